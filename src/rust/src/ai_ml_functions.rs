@@ -1,8 +1,8 @@
+use crate::builtins::{ContextFunction, NativeContext};
 use crate::environment::Value;
 use crate::errors::{KyaroError, Result};
 use std::collections::HashMap;
 use rand::prelude::*;
-use ndarray::prelude::*;
 
 pub type AiMlFunction = fn(&[Value]) -> Result<Value>;
 
@@ -67,6 +67,28 @@ pub fn get_ai_ml_functions() -> HashMap<String, AiMlFunction> {
     functions
 }
 
+/// Parameter names of the ML functions that accept keyword arguments.
+/// Optional parameters end in `?`.
+pub fn ai_ml_parameters(name: &str) -> Option<&'static [&'static str]> {
+    let parameters: &'static [&'static str] = match name {
+        "ml_train_test_split" => &["data", "labels", "test_size?", "random_state?"],
+        "ml_min_max_scale" => &["data", "feature_range?"],
+        "nn_leaky_relu" | "nn_elu" => &["x", "alpha?"],
+        "ml_minimize" => &["loss", "weights", "learning_rate?", "iterations?", "method?"],
+        _ => return None,
+    };
+    Some(parameters)
+}
+
+/// ML functions that call back into Kyaro code, such as a user loss function.
+pub fn get_ai_ml_context_functions() -> HashMap<String, ContextFunction> {
+    let mut functions = HashMap::new();
+    
+    functions.insert("ml_minimize".to_string(), ml_minimize as ContextFunction);
+    
+    functions
+}
+
 // Data preprocessing functions
 fn ml_train_test_split(args: &[Value]) -> Result<Value> {
     if args.len() < 2 || args.len() > 4 {
@@ -74,7 +96,7 @@ fn ml_train_test_split(args: &[Value]) -> Result<Value> {
     }
     
     let (data, labels) = match (&args[0], &args[1]) {
-        (Value::List(d), Value::List(l)) => (d, l),
+        (Value::List(d), Value::List(l)) => (d.borrow(), l.borrow()),
         _ => return Err(KyaroError::runtime_error("ml_train_test_split() requires two lists".to_string(), 0, 0)),
     };
     
@@ -82,25 +104,13 @@ fn ml_train_test_split(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("Data and labels must have same length".to_string(), 0, 0));
     }
     
-    let test_size = if args.len() > 2 {
-        if let Value::Number(n) = &args[2] {
-            *n
-        } else {
-            return Err(KyaroError::runtime_error("test_size must be a number".to_string(), 0, 0));
-        }
-    } else {
-        0.2
+    let test_size = match args.get(2) {
+        None | Some(Value::Null) => 0.2,
+        Some(value) => value.as_f64()
+            .ok_or_else(|| KyaroError::runtime_error("test_size must be a number".to_string(), 0, 0))?,
     };
     
-    let random_state = if args.len() > 3 {
-        if let Value::Number(n) = &args[3] {
-            Some(*n as u64)
-        } else {
-            None
-        }
-    } else {
-        None
-    };
+    let random_state = args.get(3).and_then(Value::as_f64).map(|n| n as u64);
     
     let mut rng = if let Some(seed) = random_state {
         StdRng::seed_from_u64(seed)
@@ -120,11 +130,11 @@ fn ml_train_test_split(args: &[Value]) -> Result<Value> {
     let y_train: Vec<Value> = train_indices.iter().map(|&i| labels[i].clone()).collect();
     let y_test: Vec<Value> = test_indices.iter().map(|&i| labels[i].clone()).collect();
     
-    Ok(Value::List(vec![
-        Value::List(x_train),
-        Value::List(x_test),
-        Value::List(y_train),
-        Value::List(y_test),
+    Ok(Value::list(vec![
+        Value::list(x_train),
+        Value::list(x_test),
+        Value::list(y_train),
+        Value::list(y_test),
     ]))
 }
 
@@ -134,17 +144,18 @@ fn ml_standardize(args: &[Value]) -> Result<Value> {
     }
     
     if let Value::List(data) = &args[0] {
+        let data = data.borrow();
         let mut numbers = Vec::new();
-        for val in data {
-            if let Value::Number(n) = val {
-                numbers.push(*n);
+        for val in data.iter() {
+            if let Some(n) = val.as_f64() {
+                numbers.push(n);
             } else {
                 return Err(KyaroError::runtime_error("ml_standardize() requires a list of numbers".to_string(), 0, 0));
             }
         }
         
         if numbers.is_empty() {
-            return Ok(Value::List(vec![]));
+            return Ok(Value::list(vec![]));
         }
         
         let mean = numbers.iter().sum::<f64>() / numbers.len() as f64;
@@ -152,45 +163,47 @@ fn ml_standardize(args: &[Value]) -> Result<Value> {
         let std = variance.sqrt();
         
         if std == 0.0 {
-            return Ok(Value::List(data.clone()));
+            return Ok(Value::list(data.clone()));
         }
         
         let standardized: Vec<Value> = numbers.iter()
-            .map(|x| Value::Number((x - mean) / std))
+            .map(|x| Value::Float((x - mean) / std))
             .collect();
         
-        Ok(Value::List(standardized))
+        Ok(Value::list(standardized))
     } else {
         Err(KyaroError::runtime_error("ml_standardize() requires a list".to_string(), 0, 0))
     }
 }
 
 fn ml_min_max_scale(args: &[Value]) -> Result<Value> {
-    if args.len() < 1 || args.len() > 2 {
+    if args.is_empty() || args.len() > 2 {
         return Err(KyaroError::runtime_error("ml_min_max_scale() takes 1-2 arguments".to_string(), 0, 0));
     }
     
     if let Value::List(data) = &args[0] {
+        let data = data.borrow();
         let mut numbers = Vec::new();
-        for val in data {
-            if let Value::Number(n) = val {
-                numbers.push(*n);
+        for val in data.iter() {
+            if let Some(n) = val.as_f64() {
+                numbers.push(n);
             } else {
                 return Err(KyaroError::runtime_error("ml_min_max_scale() requires a list of numbers".to_string(), 0, 0));
             }
         }
         
         if numbers.is_empty() {
-            return Ok(Value::List(vec![]));
+            return Ok(Value::list(vec![]));
         }
         
         let feature_range = if args.len() > 1 {
             if let Value::List(range) = &args[1] {
+                let range = range.borrow();
                 if range.len() != 2 {
                     return Err(KyaroError::runtime_error("feature_range must have 2 elements".to_string(), 0, 0));
                 }
-                if let (Value::Number(min), Value::Number(max)) = (&range[0], &range[1]) {
-                    (*min, *max)
+                if let (Some(min), Some(max)) = (range[0].as_f64(), range[1].as_f64()) {
+                    (min, max)
                 } else {
                     return Err(KyaroError::runtime_error("feature_range must contain numbers".to_string(), 0, 0));
                 }
@@ -205,18 +218,18 @@ fn ml_min_max_scale(args: &[Value]) -> Result<Value> {
         let max_val = numbers.iter().fold(f64::NEG_INFINITY, |a, &b| a.max(b));
         
         if min_val == max_val {
-            return Ok(Value::List(data.clone()));
+            return Ok(Value::list(data.clone()));
         }
         
         let scaled: Vec<Value> = numbers.iter()
             .map(|x| {
                 let normalized = (x - min_val) / (max_val - min_val);
                 let scaled_val = normalized * (feature_range.1 - feature_range.0) + feature_range.0;
-                Value::Number(scaled_val)
+                Value::Float(scaled_val)
             })
             .collect();
         
-        Ok(Value::List(scaled))
+        Ok(Value::list(scaled))
     } else {
         Err(KyaroError::runtime_error("ml_min_max_scale() requires a list".to_string(), 0, 0))
     }
@@ -228,23 +241,24 @@ fn ml_one_hot_encode(args: &[Value]) -> Result<Value> {
     }
     
     if let Value::List(data) = &args[0] {
+        let data = data.borrow();
         let mut unique_values = Vec::new();
-        for val in data {
+        for val in data.iter() {
             if !unique_values.contains(val) {
                 unique_values.push(val.clone());
             }
         }
         
         let mut encoded = Vec::new();
-        for val in data {
-            let mut one_hot = vec![Value::Number(0.0); unique_values.len()];
+        for val in data.iter() {
+            let mut one_hot = vec![Value::int(0); unique_values.len()];
             if let Some(index) = unique_values.iter().position(|x| x == val) {
-                one_hot[index] = Value::Number(1.0);
+                one_hot[index] = Value::int(1);
             }
-            encoded.push(Value::List(one_hot));
+            encoded.push(Value::list(one_hot));
         }
         
-        Ok(Value::List(encoded))
+        Ok(Value::list(encoded))
     } else {
         Err(KyaroError::runtime_error("ml_one_hot_encode() requires a list".to_string(), 0, 0))
     }
@@ -257,7 +271,7 @@ fn ml_euclidean_distance(args: &[Value]) -> Result<Value> {
     }
     
     let (vec1, vec2) = match (&args[0], &args[1]) {
-        (Value::List(v1), Value::List(v2)) => (v1, v2),
+        (Value::List(v1), Value::List(v2)) => (v1.borrow(), v2.borrow()),
         _ => return Err(KyaroError::runtime_error("ml_euclidean_distance() requires two lists".to_string(), 0, 0)),
     };
     
@@ -267,14 +281,14 @@ fn ml_euclidean_distance(args: &[Value]) -> Result<Value> {
     
     let mut sum_squares = 0.0;
     for (v1, v2) in vec1.iter().zip(vec2.iter()) {
-        if let (Value::Number(n1), Value::Number(n2)) = (v1, v2) {
+        if let (Some(n1), Some(n2)) = (v1.as_f64(), v2.as_f64()) {
             sum_squares += (n1 - n2).powi(2);
         } else {
             return Err(KyaroError::runtime_error("Vectors must contain numbers".to_string(), 0, 0));
         }
     }
     
-    Ok(Value::Number(sum_squares.sqrt()))
+    Ok(Value::Float(sum_squares.sqrt()))
 }
 
 fn ml_manhattan_distance(args: &[Value]) -> Result<Value> {
@@ -283,7 +297,7 @@ fn ml_manhattan_distance(args: &[Value]) -> Result<Value> {
     }
     
     let (vec1, vec2) = match (&args[0], &args[1]) {
-        (Value::List(v1), Value::List(v2)) => (v1, v2),
+        (Value::List(v1), Value::List(v2)) => (v1.borrow(), v2.borrow()),
         _ => return Err(KyaroError::runtime_error("ml_manhattan_distance() requires two lists".to_string(), 0, 0)),
     };
     
@@ -293,14 +307,14 @@ fn ml_manhattan_distance(args: &[Value]) -> Result<Value> {
     
     let mut sum_abs = 0.0;
     for (v1, v2) in vec1.iter().zip(vec2.iter()) {
-        if let (Value::Number(n1), Value::Number(n2)) = (v1, v2) {
+        if let (Some(n1), Some(n2)) = (v1.as_f64(), v2.as_f64()) {
             sum_abs += (n1 - n2).abs();
         } else {
             return Err(KyaroError::runtime_error("Vectors must contain numbers".to_string(), 0, 0));
         }
     }
     
-    Ok(Value::Number(sum_abs))
+    Ok(Value::Float(sum_abs))
 }
 
 fn ml_cosine_similarity(args: &[Value]) -> Result<Value> {
@@ -309,7 +323,7 @@ fn ml_cosine_similarity(args: &[Value]) -> Result<Value> {
     }
     
     let (vec1, vec2) = match (&args[0], &args[1]) {
-        (Value::List(v1), Value::List(v2)) => (v1, v2),
+        (Value::List(v1), Value::List(v2)) => (v1.borrow(), v2.borrow()),
         _ => return Err(KyaroError::runtime_error("ml_cosine_similarity() requires two lists".to_string(), 0, 0)),
     };
     
@@ -322,7 +336,7 @@ fn ml_cosine_similarity(args: &[Value]) -> Result<Value> {
     let mut norm2 = 0.0;
     
     for (v1, v2) in vec1.iter().zip(vec2.iter()) {
-        if let (Value::Number(n1), Value::Number(n2)) = (v1, v2) {
+        if let (Some(n1), Some(n2)) = (v1.as_f64(), v2.as_f64()) {
             dot_product += n1 * n2;
             norm1 += n1 * n1;
             norm2 += n2 * n2;
@@ -331,12 +345,12 @@ fn ml_cosine_similarity(args: &[Value]) -> Result<Value> {
         }
     }
     
-    let magnitude = (norm1.sqrt() * norm2.sqrt());
+    let magnitude = norm1.sqrt() * norm2.sqrt();
     if magnitude == 0.0 {
-        return Ok(Value::Number(0.0));
+        return Ok(Value::Float(0.0));
     }
     
-    Ok(Value::Number(dot_product / magnitude))
+    Ok(Value::Float(dot_product / magnitude))
 }
 
 // Placeholder implementations for remaining functions
@@ -354,7 +368,7 @@ fn ml_mse(args: &[Value]) -> Result<Value> {
     }
     
     let (y_true, y_pred) = match (&args[0], &args[1]) {
-        (Value::List(t), Value::List(p)) => (t, p),
+        (Value::List(t), Value::List(p)) => (t.borrow(), p.borrow()),
         _ => return Err(KyaroError::runtime_error("ml_mse() requires two lists".to_string(), 0, 0)),
     };
     
@@ -364,14 +378,14 @@ fn ml_mse(args: &[Value]) -> Result<Value> {
     
     let mut sum_squares = 0.0;
     for (true_val, pred_val) in y_true.iter().zip(y_pred.iter()) {
-        if let (Value::Number(t), Value::Number(p)) = (true_val, pred_val) {
+        if let (Some(t), Some(p)) = (true_val.as_f64(), pred_val.as_f64()) {
             sum_squares += (t - p).powi(2);
         } else {
             return Err(KyaroError::runtime_error("Arrays must contain numbers".to_string(), 0, 0));
         }
     }
     
-    Ok(Value::Number(sum_squares / y_true.len() as f64))
+    Ok(Value::Float(sum_squares / y_true.len() as f64))
 }
 
 fn ml_mae(args: &[Value]) -> Result<Value> {
@@ -380,7 +394,7 @@ fn ml_mae(args: &[Value]) -> Result<Value> {
     }
     
     let (y_true, y_pred) = match (&args[0], &args[1]) {
-        (Value::List(t), Value::List(p)) => (t, p),
+        (Value::List(t), Value::List(p)) => (t.borrow(), p.borrow()),
         _ => return Err(KyaroError::runtime_error("ml_mae() requires two lists".to_string(), 0, 0)),
     };
     
@@ -390,20 +404,20 @@ fn ml_mae(args: &[Value]) -> Result<Value> {
     
     let mut sum_abs = 0.0;
     for (true_val, pred_val) in y_true.iter().zip(y_pred.iter()) {
-        if let (Value::Number(t), Value::Number(p)) = (true_val, pred_val) {
+        if let (Some(t), Some(p)) = (true_val.as_f64(), pred_val.as_f64()) {
             sum_abs += (t - p).abs();
         } else {
             return Err(KyaroError::runtime_error("Arrays must contain numbers".to_string(), 0, 0));
         }
     }
     
-    Ok(Value::Number(sum_abs / y_true.len() as f64))
+    Ok(Value::Float(sum_abs / y_true.len() as f64))
 }
 
 fn ml_rmse(args: &[Value]) -> Result<Value> {
     let mse_result = ml_mse(args)?;
-    if let Value::Number(mse) = mse_result {
-        Ok(Value::Number(mse.sqrt()))
+    if let Some(mse) = mse_result.as_f64() {
+        Ok(Value::Float(mse.sqrt()))
     } else {
         Err(KyaroError::runtime_error("RMSE calculation error".to_string(), 0, 0))
     }
@@ -439,8 +453,8 @@ fn nn_tanh(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("nn_tanh() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(x) = &args[0] {
-        Ok(Value::Number(x.tanh()))
+    if let Some(x) = args[0].as_f64() {
+        Ok(Value::Float(x.tanh()))
     } else {
         Err(KyaroError::runtime_error("nn_tanh() requires a number".to_string(), 0, 0))
     }
@@ -448,8 +462,8 @@ fn nn_tanh(args: &[Value]) -> Result<Value> {
 
 fn nn_leaky_relu(args: &[Value]) -> Result<Value> {
     let alpha = if args.len() > 1 {
-        if let Value::Number(a) = &args[1] {
-            *a
+        if let Some(a) = args[1].as_f64() {
+            a
         } else {
             return Err(KyaroError::runtime_error("alpha must be a number".to_string(), 0, 0));
         }
@@ -457,8 +471,8 @@ fn nn_leaky_relu(args: &[Value]) -> Result<Value> {
         0.01
     };
     
-    if let Value::Number(x) = &args[0] {
-        Ok(Value::Number(if *x > 0.0 { *x } else { alpha * x }))
+    if let Some(x) = args[0].as_f64() {
+        Ok(Value::Float(if x > 0.0 { x } else { alpha * x }))
     } else {
         Err(KyaroError::runtime_error("nn_leaky_relu() requires a number".to_string(), 0, 0))
     }
@@ -466,8 +480,8 @@ fn nn_leaky_relu(args: &[Value]) -> Result<Value> {
 
 fn nn_elu(args: &[Value]) -> Result<Value> {
     let alpha = if args.len() > 1 {
-        if let Value::Number(a) = &args[1] {
-            *a
+        if let Some(a) = args[1].as_f64() {
+            a
         } else {
             return Err(KyaroError::runtime_error("alpha must be a number".to_string(), 0, 0));
         }
@@ -475,8 +489,8 @@ fn nn_elu(args: &[Value]) -> Result<Value> {
         1.0
     };
     
-    if let Value::Number(x) = &args[0] {
-        Ok(Value::Number(if *x > 0.0 { *x } else { alpha * (x.exp() - 1.0) }))
+    if let Some(x) = args[0].as_f64() {
+        Ok(Value::Float(if x > 0.0 { x } else { alpha * (x.exp() - 1.0) }))
     } else {
         Err(KyaroError::runtime_error("nn_elu() requires a number".to_string(), 0, 0))
     }
@@ -487,8 +501,8 @@ fn nn_softplus(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("nn_softplus() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(x) = &args[0] {
-        Ok(Value::Number((1.0 + x.exp()).ln()))
+    if let Some(x) = args[0].as_f64() {
+        Ok(Value::Float((1.0 + x.exp()).ln()))
     } else {
         Err(KyaroError::runtime_error("nn_softplus() requires a number".to_string(), 0, 0))
     }
@@ -547,3 +561,77 @@ fn gradient_descent_step(_args: &[Value]) -> Result<Value> {
 fn adam_step(_args: &[Value]) -> Result<Value> {
     Err(KyaroError::runtime_error("adam_step() not yet implemented".to_string(), 0, 0))
 }
+
+// Optimization with a user loss function
+fn ml_minimize(context: &mut dyn NativeContext, args: &[Value]) -> Result<Value> {
+    if args.len() < 2 || args.len() > 5 {
+        return Err(KyaroError::runtime_error("ml_minimize() takes 2-5 arguments".to_string(), 0, 0));
+    }
+    
+    let mut weights = match &args[1] {
+        Value::List(list) => list.borrow().iter()
+            .map(|value| match value.as_f64() {
+                Some(n) => Ok(n),
+                None => Err(KyaroError::runtime_error("ml_minimize() weights must be numbers".to_string(), 0, 0)),
+            })
+            .collect::<Result<Vec<f64>>>()?,
+        _ => return Err(KyaroError::runtime_error("ml_minimize() requires a list of weights".to_string(), 0, 0)),
+    };
+    
+    let number_arg = |index: usize, default: f64| match args.get(index) {
+        None | Some(Value::Null) => Ok(default),
+        Some(value) => value.as_f64().ok_or_else(|| {
+            KyaroError::runtime_error("ml_minimize() learning rate and iterations must be numbers".to_string(), 0, 0)
+        }),
+    };
+    let learning_rate = number_arg(2, 0.01)?;
+    let iterations = number_arg(3, 100.0)?.max(0.0) as usize;
+    let use_adam = match args.get(4) {
+        None | Some(Value::Null) => false,
+        Some(Value::String(method)) if method == "gd" => false,
+        Some(Value::String(method)) if method == "adam" => true,
+        Some(_) => return Err(KyaroError::runtime_error("ml_minimize() method must be \"gd\" or \"adam\"".to_string(), 0, 0)),
+    };
+    
+    let loss = |context: &mut dyn NativeContext, weights: &[f64]| -> Result<f64> {
+        let arguments = vec![Value::list(weights.iter().map(|w| Value::Float(*w)).collect())];
+        let result = context.call(&args[0], arguments)?;
+        result.as_f64().ok_or_else(|| KyaroError::runtime_error(
+            format!("ml_minimize() loss function must return a number, not {}", result.type_name()),
+            0,
+            0,
+        ))
+    };
+    
+    let (beta1, beta2, epsilon) = (0.9, 0.999, 1e-8);
+    let mut m = vec![0.0; weights.len()];
+    let mut v = vec![0.0; weights.len()];
+    
+    for t in 1..=iterations {
+        // Central-difference estimate of the gradient
+        let mut gradients = Vec::with_capacity(weights.len());
+        for i in 0..weights.len() {
+            let step = 1e-6 * weights[i].abs().max(1.0);
+            let mut probe = weights.clone();
+            probe[i] = weights[i] + step;
+            let above = loss(context, &probe)?;
+            probe[i] = weights[i] - step;
+            let below = loss(context, &probe)?;
+            gradients.push((above - below) / (2.0 * step));
+        }
+        
+        for (i, g) in gradients.into_iter().enumerate() {
+            if use_adam {
+                m[i] = beta1 * m[i] + (1.0 - beta1) * g;
+                v[i] = beta2 * v[i] + (1.0 - beta2) * g * g;
+                let m_hat = m[i] / (1.0 - f64::powi(beta1, t as i32));
+                let v_hat = v[i] / (1.0 - f64::powi(beta2, t as i32));
+                weights[i] -= learning_rate * m_hat / (v_hat.sqrt() + epsilon);
+            } else {
+                weights[i] -= learning_rate * g;
+            }
+        }
+    }
+    
+    Ok(Value::list(weights.into_iter().map(Value::Float).collect()))
+}
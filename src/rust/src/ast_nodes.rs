@@ -1,18 +1,26 @@
 use crate::token_types::TokenType;
+use num_bigint::BigInt;
+use std::rc::Rc;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ASTNode {
-    Number(NumberNode),
+    Int(IntNode),
+    Float(FloatNode),
+    Imaginary(ImaginaryNode),
     String(StringNode),
+    FString(FStringNode),
     Boolean(BooleanNode),
     Null(NullNode),
     Identifier(IdentifierNode),
     BinaryOp(BinaryOpNode),
     UnaryOp(UnaryOpNode),
     Assignment(AssignmentNode),
+    Reassignment(ReassignmentNode),
     CompoundAssignment(CompoundAssignmentNode),
     Call(CallNode),
     Function(FunctionNode),
+    /// An anonymous function expression, named `<lambda>`.
+    Lambda(FunctionNode),
     Return(ReturnNode),
     If(IfNode),
     While(WhileNode),
@@ -21,16 +29,84 @@ pub enum ASTNode {
     Continue(ContinueNode),
     Block(BlockNode),
     List(ListNode),
+    Dict(DictNode),
     Index(IndexNode),
+    Slice(SliceNode),
     MemberAccess(MemberAccessNode),
+    Class(ClassNode),
+    Import(ImportNode),
+    Try(TryNode),
+    Throw(ThrowNode),
+}
+
+/// Source position of the token a node was parsed from. Line 0 means unknown.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+}
+
+impl ASTNode {
+    /// The span of nodes that can raise runtime errors themselves.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            ASTNode::Identifier(node) => Some(node.span),
+            ASTNode::BinaryOp(node) => Some(node.span),
+            ASTNode::UnaryOp(node) => Some(node.span),
+            ASTNode::Reassignment(node) => Some(node.span),
+            ASTNode::CompoundAssignment(node) => Some(node.span),
+            ASTNode::Call(node) => Some(node.span),
+            ASTNode::Return(node) => Some(node.span),
+            ASTNode::For(node) => Some(node.span),
+            ASTNode::Break(node) => Some(node.span),
+            ASTNode::Continue(node) => Some(node.span),
+            ASTNode::Dict(node) => Some(node.span),
+            ASTNode::Index(node) => Some(node.span),
+            ASTNode::Slice(node) => Some(node.span),
+            ASTNode::MemberAccess(node) => Some(node.span),
+            ASTNode::Class(node) => Some(node.span),
+            ASTNode::Import(node) => Some(node.span),
+            ASTNode::Throw(node) => Some(node.span),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntNode {
+    pub value: BigInt,
+}
+
+impl IntNode {
+    pub fn new(value: BigInt) -> Self {
+        Self { value }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct NumberNode {
+pub struct FloatNode {
     pub value: f64,
 }
 
-impl NumberNode {
+impl FloatNode {
+    pub fn new(value: f64) -> Self {
+        Self { value }
+    }
+}
+
+/// An imaginary literal such as `4j`; `value` is the coefficient of `j`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImaginaryNode {
+    pub value: f64,
+}
+
+impl ImaginaryNode {
     pub fn new(value: f64) -> Self {
         Self { value }
     }
@@ -47,6 +123,25 @@ impl StringNode {
     }
 }
 
+/// `f"..."`: literal text and embedded expressions, concatenated when evaluated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FStringNode {
+    pub parts: Vec<FStringPart>,
+}
+
+impl FStringNode {
+    pub fn new(parts: Vec<FStringPart>) -> Self {
+        Self { parts }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FStringPart {
+    Text(String),
+    /// An embedded expression and its format spec, such as `.2f` in `{x:.2f}`.
+    Expression { value: ASTNode, spec: Option<String>, span: Span },
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct BooleanNode {
     pub value: bool,
@@ -58,7 +153,7 @@ impl BooleanNode {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct NullNode;
 
 impl NullNode {
@@ -70,11 +165,12 @@ impl NullNode {
 #[derive(Debug, Clone, PartialEq)]
 pub struct IdentifierNode {
     pub name: String,
+    pub span: Span,
 }
 
 impl IdentifierNode {
-    pub fn new(name: String) -> Self {
-        Self { name }
+    pub fn new(name: String, span: Span) -> Self {
+        Self { name, span }
     }
 }
 
@@ -83,14 +179,16 @@ pub struct BinaryOpNode {
     pub left: Box<ASTNode>,
     pub operator: TokenType,
     pub right: Box<ASTNode>,
+    pub span: Span,
 }
 
 impl BinaryOpNode {
-    pub fn new(left: ASTNode, operator: TokenType, right: ASTNode) -> Self {
+    pub fn new(left: ASTNode, operator: TokenType, right: ASTNode, span: Span) -> Self {
         Self {
             left: Box::new(left),
             operator,
             right: Box::new(right),
+            span,
         }
     }
 }
@@ -99,13 +197,15 @@ impl BinaryOpNode {
 pub struct UnaryOpNode {
     pub operator: TokenType,
     pub operand: Box<ASTNode>,
+    pub span: Span,
 }
 
 impl UnaryOpNode {
-    pub fn new(operator: TokenType, operand: ASTNode) -> Self {
+    pub fn new(operator: TokenType, operand: ASTNode, span: Span) -> Self {
         Self {
             operator,
             operand: Box::new(operand),
+            span,
         }
     }
 }
@@ -125,19 +225,40 @@ impl AssignmentNode {
     }
 }
 
+/// Assignment to an existing binding: `x = v`, `xs[i] = v` or `obj.field = v`.
+/// Unlike `let`, the target must already be defined.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReassignmentNode {
+    pub target: Box<ASTNode>,
+    pub value: Box<ASTNode>,
+    pub span: Span,
+}
+
+impl ReassignmentNode {
+    pub fn new(target: ASTNode, value: ASTNode, span: Span) -> Self {
+        Self {
+            target: Box::new(target),
+            value: Box::new(value),
+            span,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct CompoundAssignmentNode {
-    pub name: String,
+    pub target: Box<ASTNode>,
     pub operator: TokenType,
     pub value: Box<ASTNode>,
+    pub span: Span,
 }
 
 impl CompoundAssignmentNode {
-    pub fn new(name: String, operator: TokenType, value: ASTNode) -> Self {
+    pub fn new(target: ASTNode, operator: TokenType, value: ASTNode, span: Span) -> Self {
         Self {
-            name,
+            target: Box::new(target),
             operator,
             value: Box::new(value),
+            span,
         }
     }
 }
@@ -146,30 +267,52 @@ impl CompoundAssignmentNode {
 pub struct CallNode {
     pub callee: Box<ASTNode>,
     pub arguments: Vec<ASTNode>,
+    /// `name: value` arguments, after the positional ones.
+    pub keyword_arguments: Vec<(String, ASTNode)>,
+    pub span: Span,
 }
 
 impl CallNode {
-    pub fn new(callee: ASTNode, arguments: Vec<ASTNode>) -> Self {
+    pub fn new(callee: ASTNode, arguments: Vec<ASTNode>, keyword_arguments: Vec<(String, ASTNode)>, span: Span) -> Self {
         Self {
             callee: Box::new(callee),
             arguments,
+            keyword_arguments,
+            span,
         }
     }
 }
 
+/// `func f(a, b = 2, *rest, key = 1, **options)`. The parameters after
+/// `*rest` can only be passed by keyword.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ParameterList {
+    pub parameters: Vec<Parameter>,
+    /// How many of `parameters` can be passed positionally.
+    pub positional: usize,
+    pub rest: Option<String>,
+    pub keywords: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    pub name: String,
+    pub default: Option<ASTNode>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct FunctionNode {
     pub name: String,
-    pub parameters: Vec<String>,
-    pub body: Box<ASTNode>,
+    pub parameters: ParameterList,
+    pub body: Rc<ASTNode>,
 }
 
 impl FunctionNode {
-    pub fn new(name: String, parameters: Vec<String>, body: ASTNode) -> Self {
+    pub fn new(name: String, parameters: ParameterList, body: ASTNode) -> Self {
         Self {
             name,
             parameters,
-            body: Box::new(body),
+            body: Rc::new(body),
         }
     }
 }
@@ -177,12 +320,14 @@ impl FunctionNode {
 #[derive(Debug, Clone, PartialEq)]
 pub struct ReturnNode {
     pub value: Option<Box<ASTNode>>,
+    pub span: Span,
 }
 
 impl ReturnNode {
-    pub fn new(value: Option<ASTNode>) -> Self {
+    pub fn new(value: Option<ASTNode>, span: Span) -> Self {
         Self {
             value: value.map(Box::new),
+            span,
         }
     }
 }
@@ -231,33 +376,39 @@ pub struct ForNode {
     pub variable: String,
     pub iterable: Box<ASTNode>,
     pub body: Box<ASTNode>,
+    pub span: Span,
 }
 
 impl ForNode {
-    pub fn new(variable: String, iterable: ASTNode, body: ASTNode) -> Self {
+    pub fn new(variable: String, iterable: ASTNode, body: ASTNode, span: Span) -> Self {
         Self {
             variable,
             iterable: Box::new(iterable),
             body: Box::new(body),
+            span,
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct BreakNode;
+pub struct BreakNode {
+    pub span: Span,
+}
 
 impl BreakNode {
-    pub fn new() -> Self {
-        Self
+    pub fn new(span: Span) -> Self {
+        Self { span }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct ContinueNode;
+pub struct ContinueNode {
+    pub span: Span,
+}
 
 impl ContinueNode {
-    pub fn new() -> Self {
-        Self
+    pub fn new(span: Span) -> Self {
+        Self { span }
     }
 }
 
@@ -283,17 +434,59 @@ impl ListNode {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct DictNode {
+    pub entries: Vec<(ASTNode, ASTNode)>,
+    pub span: Span,
+}
+
+impl DictNode {
+    pub fn new(entries: Vec<(ASTNode, ASTNode)>, span: Span) -> Self {
+        Self { entries, span }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct IndexNode {
     pub object: Box<ASTNode>,
     pub index: Box<ASTNode>,
+    pub span: Span,
 }
 
 impl IndexNode {
-    pub fn new(object: ASTNode, index: ASTNode) -> Self {
+    pub fn new(object: ASTNode, index: ASTNode, span: Span) -> Self {
         Self {
             object: Box::new(object),
             index: Box::new(index),
+            span,
+        }
+    }
+}
+
+/// `object[start:end:step]`, where each bound may be omitted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SliceNode {
+    pub object: Box<ASTNode>,
+    pub start: Option<Box<ASTNode>>,
+    pub end: Option<Box<ASTNode>>,
+    pub step: Option<Box<ASTNode>>,
+    pub span: Span,
+}
+
+impl SliceNode {
+    pub fn new(
+        object: ASTNode,
+        start: Option<ASTNode>,
+        end: Option<ASTNode>,
+        step: Option<ASTNode>,
+        span: Span,
+    ) -> Self {
+        Self {
+            object: Box::new(object),
+            start: start.map(Box::new),
+            end: end.map(Box::new),
+            step: step.map(Box::new),
+            span,
         }
     }
 }
@@ -302,13 +495,88 @@ impl IndexNode {
 pub struct MemberAccessNode {
     pub object: Box<ASTNode>,
     pub member: String,
+    pub span: Span,
 }
 
 impl MemberAccessNode {
-    pub fn new(object: ASTNode, member: String) -> Self {
+    pub fn new(object: ASTNode, member: String, span: Span) -> Self {
         Self {
             object: Box::new(object),
             member,
+            span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassNode {
+    pub name: String,
+    pub superclass: Option<Box<ASTNode>>,
+    pub methods: Vec<FunctionNode>,
+    pub span: Span,
+}
+
+impl ClassNode {
+    pub fn new(name: String, superclass: Option<ASTNode>, methods: Vec<FunctionNode>, span: Span) -> Self {
+        Self {
+            name,
+            superclass: superclass.map(Box::new),
+            methods,
+            span,
+        }
+    }
+}
+
+/// `import name` searches for `name.kyaro`; `import "path"` names the file directly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportNode {
+    pub module: String,
+    pub is_path: bool,
+    pub span: Span,
+}
+
+impl ImportNode {
+    pub fn new(module: String, is_path: bool, span: Span) -> Self {
+        Self { module, is_path, span }
+    }
+}
+
+/// `try { } catch err { } finally { }`; at least one of `catch` and `finally` is present.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TryNode {
+    pub body: Box<ASTNode>,
+    pub catch_variable: Option<String>,
+    pub catch_body: Option<Box<ASTNode>>,
+    pub finally_body: Option<Box<ASTNode>>,
+}
+
+impl TryNode {
+    pub fn new(
+        body: ASTNode,
+        catch_variable: Option<String>,
+        catch_body: Option<ASTNode>,
+        finally_body: Option<ASTNode>,
+    ) -> Self {
+        Self {
+            body: Box::new(body),
+            catch_variable,
+            catch_body: catch_body.map(Box::new),
+            finally_body: finally_body.map(Box::new),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThrowNode {
+    pub value: Box<ASTNode>,
+    pub span: Span,
+}
+
+impl ThrowNode {
+    pub fn new(value: ASTNode, span: Span) -> Self {
+        Self {
+            value: Box::new(value),
+            span,
         }
     }
 }
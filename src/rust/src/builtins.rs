@@ -1,13 +1,45 @@
-use crate::environment::Value;
+use crate::environment::{compare_numbers, float_repr, DictKey, DictRef, ListRef, Value};
 use crate::errors::{KyaroError, Result};
+use num_bigint::BigInt;
+use num_complex::Complex64;
+use num_integer::Integer;
+use num_traits::{FromPrimitive, Pow, Signed, ToPrimitive, Zero};
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use rand::prelude::*;
-use rand_distr::*;
-use statrs::statistics::*;
-use libm::*;
+use unicode_segmentation::UnicodeSegmentation;
 
 pub type BuiltinFunction = fn(&[Value]) -> Result<Value>;
 
+/// Lets a native function call back into Kyaro code, e.g. to apply a user
+/// function to every element of a list.
+pub trait NativeContext {
+    /// Calls any callable value. Errors raised inside it keep their position.
+    fn call(&mut self, function: &Value, arguments: Vec<Value>) -> Result<Value>;
+}
+
+/// A native function that needs a [`NativeContext`] to call the functions it is given.
+pub type ContextFunction = fn(&mut dyn NativeContext, &[Value]) -> Result<Value>;
+
+/// The function pointer behind a `Value::NativeFunction`.
+#[derive(Debug, Clone, Copy)]
+pub enum NativeFn {
+    Plain(BuiltinFunction),
+    WithContext(ContextFunction),
+}
+
+impl From<BuiltinFunction> for NativeFn {
+    fn from(function: BuiltinFunction) -> Self {
+        NativeFn::Plain(function)
+    }
+}
+
+impl From<ContextFunction> for NativeFn {
+    fn from(function: ContextFunction) -> Self {
+        NativeFn::WithContext(function)
+    }
+}
+
 pub fn get_builtin_functions() -> HashMap<String, BuiltinFunction> {
     let mut functions = HashMap::new();
     
@@ -20,6 +52,7 @@ pub fn get_builtin_functions() -> HashMap<String, BuiltinFunction> {
     functions.insert("int".to_string(), builtin_int as BuiltinFunction);
     functions.insert("float".to_string(), builtin_float as BuiltinFunction);
     functions.insert("type".to_string(), builtin_type as BuiltinFunction);
+    functions.insert("format".to_string(), builtin_format as BuiltinFunction);
     
     // Collection functions
     functions.insert("len".to_string(), builtin_len as BuiltinFunction);
@@ -27,6 +60,20 @@ pub fn get_builtin_functions() -> HashMap<String, BuiltinFunction> {
     functions.insert("append".to_string(), builtin_append as BuiltinFunction);
     functions.insert("pop".to_string(), builtin_pop as BuiltinFunction);
     functions.insert("push".to_string(), builtin_push as BuiltinFunction);
+    functions.insert("insert".to_string(), builtin_insert as BuiltinFunction);
+    functions.insert("remove".to_string(), builtin_remove as BuiltinFunction);
+    functions.insert("clear".to_string(), builtin_clear as BuiltinFunction);
+    functions.insert("extend".to_string(), builtin_extend as BuiltinFunction);
+    functions.insert("reverse".to_string(), builtin_reverse as BuiltinFunction);
+    functions.insert("sort".to_string(), builtin_sort as BuiltinFunction);
+    functions.insert("copy".to_string(), builtin_copy as BuiltinFunction);
+    
+    // Dict functions
+    functions.insert("keys".to_string(), builtin_keys as BuiltinFunction);
+    functions.insert("values".to_string(), builtin_values as BuiltinFunction);
+    functions.insert("items".to_string(), builtin_items as BuiltinFunction);
+    functions.insert("get".to_string(), builtin_get as BuiltinFunction);
+    functions.insert("has_key".to_string(), builtin_has_key as BuiltinFunction);
     
     // Math functions
     functions.insert("abs".to_string(), builtin_abs as BuiltinFunction);
@@ -37,6 +84,13 @@ pub fn get_builtin_functions() -> HashMap<String, BuiltinFunction> {
     functions.insert("pow".to_string(), builtin_pow as BuiltinFunction);
     functions.insert("exp".to_string(), builtin_exp as BuiltinFunction);
     
+    // Complex number functions
+    functions.insert("complex".to_string(), builtin_complex as BuiltinFunction);
+    functions.insert("real".to_string(), builtin_real as BuiltinFunction);
+    functions.insert("imag".to_string(), builtin_imag as BuiltinFunction);
+    functions.insert("conj".to_string(), builtin_conj as BuiltinFunction);
+    functions.insert("phase".to_string(), builtin_phase as BuiltinFunction);
+    
     // Logarithmic functions
     functions.insert("log".to_string(), builtin_log as BuiltinFunction);
     functions.insert("log10".to_string(), builtin_log10 as BuiltinFunction);
@@ -99,6 +153,70 @@ pub fn get_builtin_functions() -> HashMap<String, BuiltinFunction> {
     functions
 }
 
+/// Parameter names of the builtins that accept keyword arguments.
+/// Optional parameters end in `?`.
+pub fn builtin_parameters(name: &str) -> Option<&'static [&'static str]> {
+    let parameters: &'static [&'static str] = match name {
+        "format" => &["value", "spec?"],
+        "pop" => &["list", "index?"],
+        "insert" => &["list", "index", "value"],
+        "get" => &["dict", "key", "default?"],
+        "log" => &["x", "base?"],
+        "round" => &["x", "digits?"],
+        "pow" => &["base", "exponent"],
+        "complex" => &["real?", "imag?"],
+        "split" => &["text", "separator?"],
+        "strip" => &["text", "characters?"],
+        "replace" => &["text", "old", "new", "count?"],
+        "pad_left" | "pad_right" => &["text", "width", "fill?"],
+        "map" | "filter" => &["function", "list"],
+        "reduce" => &["function", "list", "initial?"],
+        "sorted" => &["list", "key?"],
+        "all" | "any" => &["function?", "list?"],
+        _ => return None,
+    };
+    Some(parameters)
+}
+
+/// String builtins, which strings also have as methods: `s.upper()` is
+/// `upper(s)`, with the string passed as the first argument.
+pub fn get_string_functions() -> HashMap<String, BuiltinFunction> {
+    let mut functions = HashMap::new();
+    
+    functions.insert("split".to_string(), builtin_split as BuiltinFunction);
+    functions.insert("join".to_string(), builtin_join as BuiltinFunction);
+    functions.insert("strip".to_string(), builtin_strip as BuiltinFunction);
+    functions.insert("replace".to_string(), builtin_replace as BuiltinFunction);
+    functions.insert("find".to_string(), builtin_find as BuiltinFunction);
+    functions.insert("starts_with".to_string(), builtin_starts_with as BuiltinFunction);
+    functions.insert("ends_with".to_string(), builtin_ends_with as BuiltinFunction);
+    functions.insert("upper".to_string(), builtin_upper as BuiltinFunction);
+    functions.insert("lower".to_string(), builtin_lower as BuiltinFunction);
+    functions.insert("title".to_string(), builtin_title as BuiltinFunction);
+    functions.insert("pad_left".to_string(), builtin_pad_left as BuiltinFunction);
+    functions.insert("pad_right".to_string(), builtin_pad_right as BuiltinFunction);
+    functions.insert("repeat".to_string(), builtin_repeat as BuiltinFunction);
+    functions.insert("chars".to_string(), builtin_chars as BuiltinFunction);
+    functions.insert("bytes".to_string(), builtin_bytes as BuiltinFunction);
+    functions.insert("graphemes".to_string(), builtin_graphemes as BuiltinFunction);
+    
+    functions
+}
+
+/// Builtins that take functions as arguments.
+pub fn get_context_functions() -> HashMap<String, ContextFunction> {
+    let mut functions = HashMap::new();
+    
+    functions.insert("map".to_string(), builtin_map as ContextFunction);
+    functions.insert("filter".to_string(), builtin_filter as ContextFunction);
+    functions.insert("reduce".to_string(), builtin_reduce as ContextFunction);
+    functions.insert("sorted".to_string(), builtin_sorted as ContextFunction);
+    functions.insert("all".to_string(), builtin_all as ContextFunction);
+    functions.insert("any".to_string(), builtin_any as ContextFunction);
+    
+    functions
+}
+
 // I/O Functions
 fn builtin_print(args: &[Value]) -> Result<Value> {
     let output = if args.is_empty() {
@@ -115,7 +233,7 @@ fn builtin_print(args: &[Value]) -> Result<Value> {
 
 fn builtin_input(args: &[Value]) -> Result<Value> {
     if !args.is_empty() {
-        print!("{}", args[0].to_string());
+        print!("{}", args[0]);
     }
     use std::io::{self, Write};
     io::stdout().flush().unwrap();
@@ -142,10 +260,14 @@ fn builtin_int(args: &[Value]) -> Result<Value> {
     }
     
     match &args[0] {
-        Value::Number(n) => Ok(Value::Number(*n as i64 as f64)),
+        Value::Int(n) => Ok(Value::Int(n.clone())),
+        // Floats are truncated toward zero
+        Value::Float(n) => BigInt::from_f64(n.trunc())
+            .map(Value::Int)
+            .ok_or_else(|| KyaroError::runtime_error(format!("Cannot convert {} to int", float_repr(*n)), 0, 0)),
         Value::String(s) => {
-            s.parse::<i64>()
-                .map(|i| Value::Number(i as f64))
+            s.trim().parse::<BigInt>()
+                .map(Value::Int)
                 .map_err(|_| KyaroError::runtime_error("Invalid integer".to_string(), 0, 0))
         }
         _ => Err(KyaroError::runtime_error("Cannot convert to int".to_string(), 0, 0)),
@@ -158,10 +280,10 @@ fn builtin_float(args: &[Value]) -> Result<Value> {
     }
     
     match &args[0] {
-        Value::Number(n) => Ok(Value::Number(*n)),
+        Value::Int(_) | Value::Float(_) => Ok(Value::Float(args[0].as_f64().unwrap_or(f64::NAN))),
         Value::String(s) => {
-            s.parse::<f64>()
-                .map(Value::Number)
+            s.trim().parse::<f64>()
+                .map(Value::Float)
                 .map_err(|_| KyaroError::runtime_error("Invalid float".to_string(), 0, 0))
         }
         _ => Err(KyaroError::runtime_error("Cannot convert to float".to_string(), 0, 0)),
@@ -176,15 +298,306 @@ fn builtin_type(args: &[Value]) -> Result<Value> {
     let type_name = match &args[0] {
         Value::Null => "null",
         Value::Boolean(_) => "boolean",
-        Value::Number(_) => "number",
+        Value::Int(_) => "integer",
+        Value::Float(_) => "float",
+        Value::Complex(_) => "complex",
         Value::String(_) => "string",
         Value::List(_) => "list",
-        Value::Function { .. } => "function",
+        Value::Dict(_) => "dict",
+        Value::Function { .. } | Value::NativeFunction { .. } => "function",
+        Value::Class(_) => "class",
+        Value::Module(_) => "module",
+        Value::Image(_) => "image",
+        Value::BoundMethod { .. } => "method",
+        // An object's type is the name of its class
+        Value::Instance(instance) => return Ok(Value::String(instance.borrow().class.name.clone())),
     };
     
     Ok(Value::String(type_name.to_string()))
 }
 
+fn builtin_format(args: &[Value]) -> Result<Value> {
+    match args {
+        [value] => Ok(Value::String(value.to_string())),
+        [value, Value::String(spec)] => Ok(Value::String(format_value(value, spec)?)),
+        [_, _] => Err(KyaroError::runtime_error("format() spec must be a string".to_string(), 0, 0)),
+        _ => Err(KyaroError::runtime_error("format() takes 1-2 arguments".to_string(), 0, 0)),
+    }
+}
+
+/// The largest width or precision a format spec may ask for.
+const MAX_FORMAT_DIGITS: usize = 1_000_000;
+
+/// Formats a value by a Python-style format spec, as used by f-strings:
+/// `[[fill]align][sign][#][0][width][,|_][.precision][type]`.
+pub fn format_value(value: &Value, spec: &str) -> Result<String> {
+    let invalid = || KyaroError::runtime_error(format!("Invalid format specifier '{}'", spec), 0, 0);
+    let chars: Vec<char> = spec.chars().collect();
+    let mut i = 0;
+    
+    let is_align = |c: char| matches!(c, '<' | '>' | '^' | '=');
+    let (mut fill, mut align) = match chars.as_slice() {
+        [fill, align, ..] if is_align(*align) => {
+            i = 2;
+            (*fill, Some(*align))
+        }
+        [align, ..] if is_align(*align) => {
+            i = 1;
+            (' ', Some(*align))
+        }
+        _ => (' ', None),
+    };
+    
+    let sign = match chars.get(i) {
+        Some(c @ ('+' | '-' | ' ')) => {
+            i += 1;
+            *c
+        }
+        _ => '-',
+    };
+    let alternate = chars.get(i) == Some(&'#');
+    if alternate {
+        i += 1;
+    }
+    if chars.get(i) == Some(&'0') {
+        if align.is_none() {
+            fill = '0';
+            align = Some('=');
+        }
+        i += 1;
+    }
+    
+    let digits = |i: &mut usize| {
+        let start = *i;
+        while chars.get(*i).is_some_and(char::is_ascii_digit) {
+            *i += 1;
+        }
+        if start == *i {
+            return Ok(None);
+        }
+        match chars[start..*i].iter().collect::<String>().parse::<usize>() {
+            Ok(n) if n <= MAX_FORMAT_DIGITS => Ok(Some(n)),
+            _ => Err(KyaroError::runtime_error("Too many decimal digits in format string", 0, 0)),
+        }
+    };
+    let width = digits(&mut i)?.unwrap_or(0);
+    let grouping = match chars.get(i) {
+        Some(c @ (',' | '_')) => {
+            i += 1;
+            Some(*c)
+        }
+        _ => None,
+    };
+    let precision = if chars.get(i) == Some(&'.') {
+        i += 1;
+        Some(digits(&mut i)?.ok_or_else(invalid)?)
+    } else {
+        None
+    };
+    let kind = chars.get(i).copied();
+    if chars.len() > i + 1 {
+        return Err(invalid());
+    }
+    
+    // The sign and any base prefix stay to the left of '=' padding
+    let (prefix, body, default_align) = match value {
+        Value::Int(_) | Value::Float(_) => {
+            // Zero padding is grouped along with the digits, as in `00,001,234`
+            let zero_width = (fill == '0' && align == Some('=') && grouping.is_some()).then_some(width);
+            let (prefix, body) = format_number(value, kind, sign, alternate, grouping, precision, zero_width)?;
+            (prefix, body, '>')
+        }
+        Value::String(_) | Value::Boolean(_) | Value::Null => {
+            if sign != '-' || grouping.is_some() || align == Some('=') {
+                return Err(invalid());
+            }
+            if !matches!(kind, None | Some('s')) {
+                return Err(KyaroError::runtime_error(
+                    format!("Unknown format code '{}' for {}", kind.unwrap_or('s'), value.type_name()),
+                    0,
+                    0,
+                ));
+            }
+            let text = value.to_string();
+            let body = match precision {
+                Some(precision) => text.chars().take(precision).collect(),
+                None => text,
+            };
+            (String::new(), body, '<')
+        }
+        other if spec.is_empty() => (String::new(), other.to_string(), '<'),
+        other => {
+            return Err(KyaroError::runtime_error(
+                format!("unsupported format spec for {}", other.type_name()),
+                0,
+                0,
+            ));
+        }
+    };
+    
+    let length = prefix.chars().count() + body.chars().count();
+    let padding = width.saturating_sub(length);
+    let pad = |count: usize| fill.to_string().repeat(count);
+    
+    Ok(match align.unwrap_or(default_align) {
+        '<' => format!("{}{}{}", prefix, body, pad(padding)),
+        '^' => format!("{}{}{}{}", pad(padding / 2), prefix, body, pad(padding - padding / 2)),
+        '=' => format!("{}{}{}", prefix, pad(padding), body),
+        _ => format!("{}{}{}", pad(padding), prefix, body),
+    })
+}
+
+/// Formats a number for [`format_value`], returning the sign and base
+/// prefix separately from the digits. With `zero_width`, the digits are
+/// zero-padded before grouping so the whole number fills that width.
+fn format_number(
+    value: &Value,
+    kind: Option<char>,
+    sign: char,
+    alternate: bool,
+    grouping: Option<char>,
+    precision: Option<usize>,
+    zero_width: Option<usize>,
+) -> Result<(String, String)> {
+    let integer = || match value {
+        Value::Int(n) => Ok(n.magnitude().clone()),
+        _ => Err(KyaroError::runtime_error(
+            format!("Format code '{}' requires an integer", kind.unwrap_or('d')),
+            0,
+            0,
+        )),
+    };
+    
+    // As in Python, an int takes a precision only with a float presentation type
+    if matches!(value, Value::Int(_)) && precision.is_some() && matches!(kind, None | Some('d' | 'x' | 'X' | 'o' | 'b')) {
+        return Err(KyaroError::runtime_error("Precision not allowed in integer format specifier", 0, 0));
+    }
+    
+    let n = value.as_f64().unwrap_or(f64::NAN);
+    let magnitude = n.abs();
+    let (base_prefix, mut body) = match kind {
+        None | Some('f' | 'F' | 'e' | 'E' | 'g' | 'G' | '%') if !magnitude.is_finite() => {
+            let body = if magnitude.is_nan() { "nan" } else { "inf" };
+            let body = if kind == Some('%') { format!("{}%", body) } else { body.to_string() };
+            ("", if matches!(kind, Some('F' | 'E' | 'G')) { body.to_uppercase() } else { body })
+        }
+        None if precision.is_none() => (
+            "",
+            match value {
+                Value::Int(n) => n.magnitude().to_string(),
+                _ => float_repr(magnitude),
+            },
+        ),
+        None | Some('g' | 'G') => {
+            let body = format_general(magnitude, precision.unwrap_or(6), alternate);
+            ("", if kind == Some('G') { body.to_uppercase() } else { body })
+        }
+        Some('f' | 'F') => ("", format!("{:.*}", precision.unwrap_or(6), magnitude)),
+        Some('e' | 'E') => {
+            let body = format_exponent(magnitude, precision.unwrap_or(6));
+            ("", if kind == Some('E') { body.to_uppercase() } else { body })
+        }
+        Some('%') => ("", format!("{:.*}%", precision.unwrap_or(6), magnitude * 100.0)),
+        Some('d') => ("", integer()?.to_string()),
+        Some('x') => ("0x", format!("{:x}", integer()?)),
+        Some('X') => ("0X", format!("{:X}", integer()?)),
+        Some('o') => ("0o", format!("{:o}", integer()?)),
+        Some('b') => ("0b", format!("{:b}", integer()?)),
+        Some(other) => {
+            return Err(KyaroError::runtime_error(format!("Unknown format code '{}' for number", other), 0, 0));
+        }
+    };
+    
+    let negative = match value {
+        Value::Int(n) => n.is_negative(),
+        _ => n.is_sign_negative() && !n.is_nan(),
+    };
+    let sign = if negative {
+        "-"
+    } else {
+        match sign {
+            '+' => "+",
+            ' ' => " ",
+            _ => "",
+        }
+    };
+    let base_prefix = if alternate { base_prefix } else { "" };
+    let prefix = format!("{}{}", sign, base_prefix);
+    
+    if let Some(separator) = grouping {
+        // Decimal digits group by thousands, other bases by four
+        let other_base = matches!(kind, Some('x' | 'X' | 'o' | 'b'));
+        let group = if other_base { 4 } else { 3 };
+        let split = body
+            .find(|c: char| if other_base { !c.is_ascii_hexdigit() } else { !c.is_ascii_digit() })
+            .unwrap_or(body.len());
+        let (whole, rest) = body.split_at(split);
+        let mut digits: Vec<char> = whole.chars().collect();
+        if let Some(width) = zero_width {
+            let target = width.saturating_sub(prefix.chars().count() + rest.chars().count());
+            let mut length = digits.len();
+            while length + (length.max(1) - 1) / group < target {
+                length += 1;
+            }
+            digits.splice(0..0, std::iter::repeat_n('0', length - digits.len()));
+        }
+        let mut grouped = String::new();
+        for (index, digit) in digits.iter().enumerate() {
+            if index > 0 && (digits.len() - index).is_multiple_of(group) {
+                grouped.push(separator);
+            }
+            grouped.push(*digit);
+        }
+        body = grouped + rest;
+    }
+    
+    Ok((prefix, body))
+}
+
+/// Scientific notation with a signed, two-digit exponent, like Python's `e` format.
+fn format_exponent(n: f64, precision: usize) -> String {
+    let formatted = format!("{:.*e}", precision, n);
+    match formatted.split_once('e') {
+        Some((mantissa, exponent)) => {
+            let (sign, digits) = match exponent.strip_prefix('-') {
+                Some(digits) => ('-', digits),
+                None => ('+', exponent),
+            };
+            format!("{}e{}{:0>2}", mantissa, sign, digits)
+        }
+        None => formatted,
+    }
+}
+
+/// Python's `g` format: fixed or scientific notation depending on the
+/// exponent, without trailing zeros unless `alternate` is set.
+fn format_general(n: f64, precision: usize, alternate: bool) -> String {
+    if !n.is_finite() {
+        return n.to_string();
+    }
+    
+    let precision = precision.max(1);
+    let exponent = if n == 0.0 { 0 } else { n.log10().floor() as i32 };
+    let strip = |text: String| {
+        if alternate || !text.contains('.') {
+            text
+        } else {
+            text.trim_end_matches('0').trim_end_matches('.').to_string()
+        }
+    };
+    
+    if exponent < -4 || exponent >= precision as i32 {
+        let formatted = format_exponent(n, precision - 1);
+        match formatted.split_once('e') {
+            Some((mantissa, exponent)) => format!("{}e{}", strip(mantissa.to_string()), exponent),
+            None => formatted,
+        }
+    } else {
+        let decimals = (precision as i32 - 1 - exponent).max(0) as usize;
+        strip(format!("{:.*}", decimals, n))
+    }
+}
+
 // Collection functions
 fn builtin_len(args: &[Value]) -> Result<Value> {
     if args.len() != 1 {
@@ -192,74 +605,545 @@ fn builtin_len(args: &[Value]) -> Result<Value> {
     }
     
     match &args[0] {
-        Value::String(s) => Ok(Value::Number(s.len() as f64)),
-        Value::List(l) => Ok(Value::Number(l.len() as f64)),
+        Value::String(s) => Ok(Value::int(s.chars().count())),
+        Value::List(l) => Ok(Value::int(l.borrow().len())),
+        Value::Dict(d) => Ok(Value::int(d.borrow().len())),
         _ => Err(KyaroError::runtime_error("len() not supported for this type".to_string(), 0, 0)),
     }
 }
 
 fn builtin_range(args: &[Value]) -> Result<Value> {
-    let (start, end, step) = match args.len() {
-        1 => {
-            if let Value::Number(n) = &args[0] {
-                (0.0, *n, 1.0)
-            } else {
-                return Err(KyaroError::runtime_error("range() argument must be a number".to_string(), 0, 0));
-            }
+    Ok(Value::list(range_iter(args)?.collect()))
+}
+
+/// Lazily yields the values of `range(...)`, so loops over large ranges
+/// never have to materialise the whole list.
+pub struct RangeIter {
+    current: i64,
+    end: i64,
+    step: i64,
+}
+
+impl Iterator for RangeIter {
+    type Item = Value;
+    
+    fn next(&mut self) -> Option<Value> {
+        let in_range = if self.step > 0 {
+            self.current < self.end
+        } else {
+            self.current > self.end
+        };
+        
+        if !in_range {
+            return None;
         }
-        2 => {
-            if let (Value::Number(start), Value::Number(end)) = (&args[0], &args[1]) {
-                (*start, *end, 1.0)
-            } else {
-                return Err(KyaroError::runtime_error("range() arguments must be numbers".to_string(), 0, 0));
-            }
+        
+        let value = Value::int(self.current);
+        // Stepping past the largest int simply ends the range
+        self.current = self.current.checked_add(self.step).unwrap_or(self.end);
+        Some(value)
+    }
+}
+
+pub fn range_iter(args: &[Value]) -> Result<RangeIter> {
+    let bound = |value: &Value| match value {
+        Value::Int(n) => n.to_i64().ok_or_else(|| {
+            KyaroError::runtime_error(format!("range() argument {} is too large", n), 0, 0)
+        }),
+        other => Err(KyaroError::runtime_error(
+            format!("range() arguments must be integers, not {}", other.type_name()),
+            0,
+            0,
+        )),
+    };
+    
+    let (start, end, step) = match args {
+        [end] => (0, bound(end)?, 1),
+        [start, end] => (bound(start)?, bound(end)?, 1),
+        [start, end, step] => (bound(start)?, bound(end)?, bound(step)?),
+        _ => return Err(KyaroError::runtime_error("range() takes 1 to 3 arguments".to_string(), 0, 0)),
+    };
+    
+    if step == 0 {
+        return Err(KyaroError::runtime_error("range() step must not be zero".to_string(), 0, 0));
+    }
+    
+    Ok(RangeIter { current: start, end, step })
+}
+
+/// The list a collection builtin operates on in place.
+fn list_arg<'a>(value: &'a Value, name: &str) -> Result<&'a ListRef> {
+    match value {
+        Value::List(list) => Ok(list),
+        _ => Err(KyaroError::runtime_error(format!("{}() requires a list", name), 0, 0)),
+    }
+}
+
+fn integer_arg(value: &Value, name: &str) -> Result<i64> {
+    match value {
+        Value::Int(n) => n.to_i64().ok_or_else(|| {
+            KyaroError::runtime_error(format!("{}() index {} is too large", name, n), 0, 0)
+        }),
+        _ => Err(KyaroError::runtime_error(format!("{}() index must be an integer", name), 0, 0)),
+    }
+}
+
+/// An int argument that fits in an `i64`.
+fn integer_value(value: &Value) -> Option<i64> {
+    match value {
+        Value::Int(n) => n.to_i64(),
+        _ => None,
+    }
+}
+
+/// Converts a float that was already rounded to an int.
+fn float_to_int(n: f64) -> Result<Value> {
+    BigInt::from_f64(n)
+        .map(Value::Int)
+        .ok_or_else(|| KyaroError::runtime_error(format!("Cannot convert {} to an integer", float_repr(n)), 0, 0))
+}
+
+fn builtin_append(args: &[Value]) -> Result<Value> {
+    if args.len() != 2 {
+        return Err(KyaroError::runtime_error("append() takes exactly two arguments".to_string(), 0, 0));
+    }
+    
+    list_arg(&args[0], "append")?.borrow_mut().push(args[1].clone());
+    Ok(Value::Null)
+}
+
+fn builtin_pop(args: &[Value]) -> Result<Value> {
+    if args.is_empty() || args.len() > 2 {
+        return Err(KyaroError::runtime_error("pop() takes 1-2 arguments".to_string(), 0, 0));
+    }
+    
+    let mut list = list_arg(&args[0], "pop")?.borrow_mut();
+    if list.is_empty() {
+        return Err(KyaroError::runtime_error("pop from empty list".to_string(), 0, 0));
+    }
+    
+    let index = if args.len() > 1 { integer_arg(&args[1], "pop")? } else { -1 };
+    let resolved = if index < 0 { index + list.len() as i64 } else { index };
+    if resolved < 0 || resolved >= list.len() as i64 {
+        return Err(KyaroError::runtime_error("pop index out of range".to_string(), 0, 0));
+    }
+    
+    Ok(list.remove(resolved as usize))
+}
+
+fn builtin_push(args: &[Value]) -> Result<Value> {
+    if args.len() != 2 {
+        return Err(KyaroError::runtime_error("push() takes exactly two arguments".to_string(), 0, 0));
+    }
+    
+    list_arg(&args[0], "push")?.borrow_mut().push(args[1].clone());
+    Ok(Value::Null)
+}
+
+fn builtin_insert(args: &[Value]) -> Result<Value> {
+    if args.len() != 3 {
+        return Err(KyaroError::runtime_error("insert() takes exactly three arguments".to_string(), 0, 0));
+    }
+    
+    let mut list = list_arg(&args[0], "insert")?.borrow_mut();
+    let index = integer_arg(&args[1], "insert")?;
+    
+    // Like Python, out-of-range positions insert at the nearest end
+    let len = list.len() as i64;
+    let position = if index < 0 { (index + len).max(0) } else { index.min(len) };
+    list.insert(position as usize, args[2].clone());
+    Ok(Value::Null)
+}
+
+fn builtin_remove(args: &[Value]) -> Result<Value> {
+    if args.len() != 2 {
+        return Err(KyaroError::runtime_error("remove() takes exactly two arguments".to_string(), 0, 0));
+    }
+    
+    // The value may contain the list itself, so it is compared under a shared borrow
+    let list = list_arg(&args[0], "remove")?;
+    let position = list.borrow().iter().position(|item| *item == args[1]);
+    match position {
+        Some(index) => {
+            list.borrow_mut().remove(index);
+            Ok(Value::Null)
         }
-        3 => {
-            if let (Value::Number(start), Value::Number(end), Value::Number(step)) = (&args[0], &args[1], &args[2]) {
-                (*start, *end, *step)
-            } else {
-                return Err(KyaroError::runtime_error("range() arguments must be numbers".to_string(), 0, 0));
+        None => Err(KyaroError::runtime_error("remove(): value not in list".to_string(), 0, 0)),
+    }
+}
+
+fn builtin_clear(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(KyaroError::runtime_error("clear() takes exactly one argument".to_string(), 0, 0));
+    }
+    
+    list_arg(&args[0], "clear")?.borrow_mut().clear();
+    Ok(Value::Null)
+}
+
+fn builtin_extend(args: &[Value]) -> Result<Value> {
+    if args.len() != 2 {
+        return Err(KyaroError::runtime_error("extend() takes exactly two arguments".to_string(), 0, 0));
+    }
+    
+    let target = list_arg(&args[0], "extend")?;
+    // Copy the items first so a list can be extended with itself
+    let items = list_arg(&args[1], "extend")?.borrow().clone();
+    target.borrow_mut().extend(items);
+    Ok(Value::Null)
+}
+
+fn builtin_reverse(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(KyaroError::runtime_error("reverse() takes exactly one argument".to_string(), 0, 0));
+    }
+    
+    list_arg(&args[0], "reverse")?.borrow_mut().reverse();
+    Ok(Value::Null)
+}
+
+fn builtin_sort(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(KyaroError::runtime_error("sort() takes exactly one argument".to_string(), 0, 0));
+    }
+    
+    let list = list_arg(&args[0], "sort")?;
+    let mut items = list.borrow().clone();
+    sort_by_value(&mut items, |item| item)?;
+    
+    *list.borrow_mut() = items;
+    Ok(Value::Null)
+}
+
+/// Stable sort on the value `key` picks out of each item.
+fn sort_by_value<T>(items: &mut [T], key: impl Fn(&T) -> &Value) -> Result<()> {
+    // Sorting stops being meaningful at the first unorderable pair. NaN is
+    // unordered but not an error, and is left where it falls.
+    let mut error = None;
+    items.sort_by(|a, b| match key(a).compare(key(b), "<") {
+        Ok(ordering) => ordering.unwrap_or(Ordering::Equal),
+        Err(e) => {
+            error.get_or_insert(e);
+            Ordering::Equal
+        }
+    });
+    
+    match error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+fn builtin_copy(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(KyaroError::runtime_error("copy() takes exactly one argument".to_string(), 0, 0));
+    }
+    
+    Ok(Value::list(list_arg(&args[0], "copy")?.borrow().clone()))
+}
+
+// Dict functions
+fn dict_arg<'a>(value: &'a Value, name: &str) -> Result<&'a DictRef> {
+    match value {
+        Value::Dict(dict) => Ok(dict),
+        _ => Err(KyaroError::runtime_error(format!("{}() requires a dict", name), 0, 0)),
+    }
+}
+
+fn builtin_keys(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(KyaroError::runtime_error("keys() takes exactly one argument".to_string(), 0, 0));
+    }
+    
+    let dict = dict_arg(&args[0], "keys")?.borrow();
+    Ok(Value::list(dict.keys().map(DictKey::to_value).collect()))
+}
+
+fn builtin_values(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(KyaroError::runtime_error("values() takes exactly one argument".to_string(), 0, 0));
+    }
+    
+    let dict = dict_arg(&args[0], "values")?.borrow();
+    Ok(Value::list(dict.values().cloned().collect()))
+}
+
+fn builtin_items(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(KyaroError::runtime_error("items() takes exactly one argument".to_string(), 0, 0));
+    }
+    
+    let dict = dict_arg(&args[0], "items")?.borrow();
+    let items = dict.iter()
+        .map(|(key, value)| Value::list(vec![key.to_value(), value.clone()]))
+        .collect();
+    Ok(Value::list(items))
+}
+
+fn builtin_get(args: &[Value]) -> Result<Value> {
+    if args.len() < 2 || args.len() > 3 {
+        return Err(KyaroError::runtime_error("get() takes 2-3 arguments".to_string(), 0, 0));
+    }
+    
+    let dict = dict_arg(&args[0], "get")?.borrow();
+    let default = args.get(2).cloned().unwrap_or(Value::Null);
+    Ok(dict.get(&DictKey::from_value(&args[1])?).cloned().unwrap_or(default))
+}
+
+fn builtin_has_key(args: &[Value]) -> Result<Value> {
+    if args.len() != 2 {
+        return Err(KyaroError::runtime_error("has_key() takes exactly two arguments".to_string(), 0, 0));
+    }
+    
+    let dict = dict_arg(&args[0], "has_key")?.borrow();
+    Ok(Value::Boolean(dict.contains_key(&DictKey::from_value(&args[1])?)))
+}
+
+// String functions. Lengths, positions and widths count Unicode scalar
+// values, the same units string indexing uses.
+fn string_arg<'a>(value: &'a Value, name: &str) -> Result<&'a str> {
+    match value {
+        Value::String(s) => Ok(s),
+        _ => Err(KyaroError::runtime_error(format!("{}() requires a string", name), 0, 0)),
+    }
+}
+
+/// An optional argument, treating `null` like an omitted one.
+fn optional_arg(args: &[Value], index: usize) -> Option<&Value> {
+    args.get(index).filter(|value| !matches!(value, Value::Null))
+}
+
+fn builtin_split(args: &[Value]) -> Result<Value> {
+    if args.is_empty() || args.len() > 2 {
+        return Err(KyaroError::runtime_error("split() takes 1-2 arguments".to_string(), 0, 0));
+    }
+    
+    let text = string_arg(&args[0], "split")?;
+    let parts: Vec<Value> = match optional_arg(args, 1) {
+        // Without a separator, runs of whitespace separate the parts
+        None => text.split_whitespace().map(|part| Value::String(part.to_string())).collect(),
+        Some(separator) => {
+            let separator = string_arg(separator, "split")?;
+            if separator.is_empty() {
+                return Err(KyaroError::runtime_error("split() separator must not be empty".to_string(), 0, 0));
             }
+            text.split(separator).map(|part| Value::String(part.to_string())).collect()
         }
-        _ => return Err(KyaroError::runtime_error("range() takes 1 to 3 arguments".to_string(), 0, 0)),
     };
     
-    let mut result = Vec::new();
-    let mut current = start;
+    Ok(Value::list(parts))
+}
+
+fn builtin_join(args: &[Value]) -> Result<Value> {
+    if args.len() != 2 {
+        return Err(KyaroError::runtime_error("join() takes exactly two arguments".to_string(), 0, 0));
+    }
+    
+    let separator = string_arg(&args[0], "join")?;
+    let items = list_arg(&args[1], "join")?.borrow();
+    let parts = items
+        .iter()
+        .map(|item| match item {
+            Value::String(s) => Ok(s.as_str()),
+            other => Err(KyaroError::runtime_error(
+                format!("join() items must be strings, not {}", other.type_name()),
+                0,
+                0,
+            )),
+        })
+        .collect::<Result<Vec<&str>>>()?;
+    
+    Ok(Value::String(parts.join(separator)))
+}
+
+fn builtin_strip(args: &[Value]) -> Result<Value> {
+    if args.is_empty() || args.len() > 2 {
+        return Err(KyaroError::runtime_error("strip() takes 1-2 arguments".to_string(), 0, 0));
+    }
     
-    if step > 0.0 {
-        while current < end {
-            result.push(Value::Number(current));
-            current += step;
+    let text = string_arg(&args[0], "strip")?;
+    let stripped = match optional_arg(args, 1) {
+        None => text.trim(),
+        Some(characters) => {
+            let characters: Vec<char> = string_arg(characters, "strip")?.chars().collect();
+            text.trim_matches(characters.as_slice())
         }
-    } else if step < 0.0 {
-        while current > end {
-            result.push(Value::Number(current));
-            current += step;
+    };
+    
+    Ok(Value::String(stripped.to_string()))
+}
+
+fn builtin_replace(args: &[Value]) -> Result<Value> {
+    if args.len() < 3 || args.len() > 4 {
+        return Err(KyaroError::runtime_error("replace() takes 3-4 arguments".to_string(), 0, 0));
+    }
+    
+    let text = string_arg(&args[0], "replace")?;
+    let old = string_arg(&args[1], "replace")?;
+    let new = string_arg(&args[2], "replace")?;
+    
+    // A negative count, like an omitted one, replaces every occurrence
+    let replaced = match optional_arg(args, 3) {
+        None => text.replace(old, new),
+        Some(Value::Int(count)) if count.is_negative() => text.replace(old, new),
+        Some(Value::Int(count)) => text.replacen(old, new, count.to_usize().unwrap_or(usize::MAX)),
+        Some(_) => return Err(KyaroError::runtime_error("replace() count must be an integer".to_string(), 0, 0)),
+    };
+    
+    Ok(Value::String(replaced))
+}
+
+fn builtin_find(args: &[Value]) -> Result<Value> {
+    if args.len() != 2 {
+        return Err(KyaroError::runtime_error("find() takes exactly two arguments".to_string(), 0, 0));
+    }
+    
+    let text = string_arg(&args[0], "find")?;
+    let substring = string_arg(&args[1], "find")?;
+    
+    match text.find(substring) {
+        Some(offset) => Ok(Value::int(text[..offset].chars().count())),
+        None => Ok(Value::int(-1)),
+    }
+}
+
+fn builtin_starts_with(args: &[Value]) -> Result<Value> {
+    if args.len() != 2 {
+        return Err(KyaroError::runtime_error("starts_with() takes exactly two arguments".to_string(), 0, 0));
+    }
+    
+    let text = string_arg(&args[0], "starts_with")?;
+    Ok(Value::Boolean(text.starts_with(string_arg(&args[1], "starts_with")?)))
+}
+
+fn builtin_ends_with(args: &[Value]) -> Result<Value> {
+    if args.len() != 2 {
+        return Err(KyaroError::runtime_error("ends_with() takes exactly two arguments".to_string(), 0, 0));
+    }
+    
+    let text = string_arg(&args[0], "ends_with")?;
+    Ok(Value::Boolean(text.ends_with(string_arg(&args[1], "ends_with")?)))
+}
+
+fn builtin_upper(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(KyaroError::runtime_error("upper() takes exactly one argument".to_string(), 0, 0));
+    }
+    
+    Ok(Value::String(string_arg(&args[0], "upper")?.to_uppercase()))
+}
+
+fn builtin_lower(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(KyaroError::runtime_error("lower() takes exactly one argument".to_string(), 0, 0));
+    }
+    
+    Ok(Value::String(string_arg(&args[0], "lower")?.to_lowercase()))
+}
+
+fn builtin_title(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(KyaroError::runtime_error("title() takes exactly one argument".to_string(), 0, 0));
+    }
+    
+    // As in Python, a letter starts a word unless it follows another letter
+    let mut result = String::new();
+    let mut in_word = false;
+    for c in string_arg(&args[0], "title")?.chars() {
+        if in_word {
+            result.extend(c.to_lowercase());
+        } else {
+            result.extend(c.to_uppercase());
         }
+        in_word = c.is_uppercase() || c.is_lowercase();
     }
     
-    Ok(Value::List(result))
+    Ok(Value::String(result))
 }
 
-fn builtin_append(args: &[Value]) -> Result<Value> {
+/// Pads `args[0]` to `args[1]` characters with the optional fill character.
+fn pad(args: &[Value], name: &str, left: bool) -> Result<Value> {
+    if args.len() < 2 || args.len() > 3 {
+        return Err(KyaroError::runtime_error(format!("{}() takes 2-3 arguments", name), 0, 0));
+    }
+    
+    let text = string_arg(&args[0], name)?;
+    let width = match &args[1] {
+        Value::Int(width) => width.to_usize().unwrap_or(if width.is_negative() { 0 } else { usize::MAX }),
+        _ => return Err(KyaroError::runtime_error(format!("{}() width must be an integer", name), 0, 0)),
+    };
+    let fill = match optional_arg(args, 2) {
+        None => ' ',
+        Some(fill) => {
+            let mut chars = string_arg(fill, name)?.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => {
+                    return Err(KyaroError::runtime_error(
+                        format!("{}() fill must be exactly one character", name),
+                        0,
+                        0,
+                    ))
+                }
+            }
+        }
+    };
+    
+    let padding: String = std::iter::repeat_n(fill, width.saturating_sub(text.chars().count())).collect();
+    Ok(Value::String(if left { padding + text } else { text.to_string() + &padding }))
+}
+
+fn builtin_pad_left(args: &[Value]) -> Result<Value> {
+    pad(args, "pad_left", true)
+}
+
+fn builtin_pad_right(args: &[Value]) -> Result<Value> {
+    pad(args, "pad_right", false)
+}
+
+fn builtin_repeat(args: &[Value]) -> Result<Value> {
     if args.len() != 2 {
-        return Err(KyaroError::runtime_error("append() takes exactly two arguments".to_string(), 0, 0));
+        return Err(KyaroError::runtime_error("repeat() takes exactly two arguments".to_string(), 0, 0));
     }
     
-    // Note: This is a simplified implementation
-    // In a real implementation, we'd need mutable references
-    Err(KyaroError::runtime_error("append() requires mutable list reference".to_string(), 0, 0))
+    let text = string_arg(&args[0], "repeat")?;
+    let count = match &args[1] {
+        Value::Int(count) if count.is_negative() => 0,
+        Value::Int(count) => count.to_usize().ok_or_else(|| {
+            KyaroError::runtime_error(format!("Cannot repeat a sequence {} times", count), 0, 0)
+        })?,
+        _ => return Err(KyaroError::runtime_error("repeat() count must be an integer".to_string(), 0, 0)),
+    };
+    
+    Ok(Value::String(text.repeat(count)))
 }
 
-fn builtin_pop(args: &[Value]) -> Result<Value> {
-    // Similar to append, requires mutable reference
-    Err(KyaroError::runtime_error("pop() requires mutable list reference".to_string(), 0, 0))
+fn builtin_chars(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(KyaroError::runtime_error("chars() takes exactly one argument".to_string(), 0, 0));
+    }
+    
+    let text = string_arg(&args[0], "chars")?;
+    Ok(Value::list(text.chars().map(|c| Value::String(c.to_string())).collect()))
 }
 
-fn builtin_push(args: &[Value]) -> Result<Value> {
-    // Similar to append
-    Err(KyaroError::runtime_error("push() requires mutable list reference".to_string(), 0, 0))
+fn builtin_bytes(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(KyaroError::runtime_error("bytes() takes exactly one argument".to_string(), 0, 0));
+    }
+    
+    let text = string_arg(&args[0], "bytes")?;
+    Ok(Value::list(text.bytes().map(Value::int).collect()))
+}
+
+fn builtin_graphemes(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(KyaroError::runtime_error("graphemes() takes exactly one argument".to_string(), 0, 0));
+    }
+    
+    // Extended grapheme clusters: what a reader sees as one character
+    let text = string_arg(&args[0], "graphemes")?;
+    Ok(Value::list(text.graphemes(true).map(|g| Value::String(g.to_string())).collect()))
 }
 
 // Math functions
@@ -268,10 +1152,11 @@ fn builtin_abs(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("abs() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        Ok(Value::Number(n.abs()))
-    } else {
-        Err(KyaroError::runtime_error("abs() requires a number".to_string(), 0, 0))
+    match &args[0] {
+        Value::Int(n) => Ok(Value::Int(n.abs())),
+        Value::Float(n) => Ok(Value::Float(n.abs())),
+        Value::Complex(n) => Ok(Value::Float(n.norm())),
+        _ => Err(KyaroError::runtime_error("abs() requires a number".to_string(), 0, 0)),
     }
 }
 
@@ -282,16 +1167,15 @@ fn builtin_min(args: &[Value]) -> Result<Value> {
     
     if args.len() == 1 {
         if let Value::List(list) = &args[0] {
+            let list = list.borrow();
             if list.is_empty() {
                 return Err(KyaroError::runtime_error("min() of empty list".to_string(), 0, 0));
             }
             
             let mut min_val = &list[0];
             for val in list.iter().skip(1) {
-                if let (Value::Number(a), Value::Number(b)) = (val, min_val) {
-                    if a < b {
-                        min_val = val;
-                    }
+                if compare_numbers(val, min_val) == Some(Ordering::Less) {
+                    min_val = val;
                 }
             }
             return Ok(min_val.clone());
@@ -300,10 +1184,8 @@ fn builtin_min(args: &[Value]) -> Result<Value> {
     
     let mut min_val = &args[0];
     for val in args.iter().skip(1) {
-        if let (Value::Number(a), Value::Number(b)) = (val, min_val) {
-            if a < b {
-                min_val = val;
-            }
+        if compare_numbers(val, min_val) == Some(Ordering::Less) {
+            min_val = val;
         }
     }
     
@@ -317,16 +1199,15 @@ fn builtin_max(args: &[Value]) -> Result<Value> {
     
     if args.len() == 1 {
         if let Value::List(list) = &args[0] {
+            let list = list.borrow();
             if list.is_empty() {
                 return Err(KyaroError::runtime_error("max() of empty list".to_string(), 0, 0));
             }
             
             let mut max_val = &list[0];
             for val in list.iter().skip(1) {
-                if let (Value::Number(a), Value::Number(b)) = (val, max_val) {
-                    if a > b {
-                        max_val = val;
-                    }
+                if compare_numbers(val, max_val) == Some(Ordering::Greater) {
+                    max_val = val;
                 }
             }
             return Ok(max_val.clone());
@@ -335,10 +1216,8 @@ fn builtin_max(args: &[Value]) -> Result<Value> {
     
     let mut max_val = &args[0];
     for val in args.iter().skip(1) {
-        if let (Value::Number(a), Value::Number(b)) = (val, max_val) {
-            if a > b {
-                max_val = val;
-            }
+        if compare_numbers(val, max_val) == Some(Ordering::Greater) {
+            max_val = val;
         }
     }
     
@@ -351,15 +1230,19 @@ fn builtin_sum(args: &[Value]) -> Result<Value> {
     }
     
     if let Value::List(list) = &args[0] {
-        let mut total = 0.0;
-        for val in list {
-            if let Value::Number(n) = val {
-                total += n;
-            } else {
-                return Err(KyaroError::runtime_error("sum() requires a list of numbers".to_string(), 0, 0));
-            }
+        let list = list.borrow();
+        // Ints add exactly until the first float
+        let mut total = Value::int(0);
+        for val in list.iter() {
+            total = match (&total, val) {
+                (Value::Int(a), Value::Int(b)) => Value::Int(a + b),
+                _ => match (total.as_f64(), val.as_f64()) {
+                    (Some(a), Some(b)) => Value::Float(a + b),
+                    _ => return Err(KyaroError::runtime_error("sum() requires a list of numbers".to_string(), 0, 0)),
+                },
+            };
         }
-        Ok(Value::Number(total))
+        Ok(total)
     } else {
         Err(KyaroError::runtime_error("sum() requires a list".to_string(), 0, 0))
     }
@@ -370,13 +1253,13 @@ fn builtin_sqrt(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("sqrt() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        if *n < 0.0 {
-            return Err(KyaroError::runtime_error("sqrt() of negative number".to_string(), 0, 0));
-        }
-        Ok(Value::Number(n.sqrt()))
-    } else {
-        Err(KyaroError::runtime_error("sqrt() requires a number".to_string(), 0, 0))
+    // Negative numbers have an imaginary root
+    match (&args[0], args[0].as_f64()) {
+        (_, Some(n)) if n >= 0.0 => Ok(Value::Float(n.sqrt())),
+        (value, _) => match value.as_complex() {
+            Some(n) => Ok(Value::Complex(n.sqrt())),
+            None => Err(KyaroError::runtime_error("sqrt() requires a number".to_string(), 0, 0)),
+        },
     }
 }
 
@@ -385,10 +1268,22 @@ fn builtin_pow(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("pow() takes exactly two arguments".to_string(), 0, 0));
     }
     
-    if let (Value::Number(base), Value::Number(exp)) = (&args[0], &args[1]) {
-        Ok(Value::Number(base.powf(*exp)))
-    } else {
-        Err(KyaroError::runtime_error("pow() requires numbers".to_string(), 0, 0))
+    // Matches `**`: an int to a non-negative int power stays exact
+    if let (Value::Int(base), Value::Int(exp)) = (&args[0], &args[1]) {
+        if let Some(exp) = exp.to_u32() {
+            return Ok(Value::Int(Pow::pow(base, exp)));
+        }
+    }
+    
+    // Complex operands, or a negative base to a fractional power, give a complex result
+    match (args[0].as_f64(), args[1].as_f64()) {
+        (Some(base), Some(exp)) if base >= 0.0 || !exp.is_finite() || exp.fract() == 0.0 => {
+            Ok(Value::Float(base.powf(exp)))
+        }
+        _ => match (args[0].as_complex(), args[1].as_complex()) {
+            (Some(base), Some(exp)) => complex_pow(base, exp).map(Value::Complex),
+            _ => Err(KyaroError::runtime_error("pow() requires numbers".to_string(), 0, 0)),
+        },
     }
 }
 
@@ -397,21 +1292,133 @@ fn builtin_exp(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("exp() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        Ok(Value::Number(n.exp()))
+    match &args[0] {
+        Value::Complex(n) => Ok(Value::Complex(n.exp())),
+        value => match value.as_f64() {
+            Some(n) => Ok(Value::Float(n.exp())),
+            None => Err(KyaroError::runtime_error("exp() requires a number".to_string(), 0, 0)),
+        },
+    }
+}
+
+// Complex number functions
+fn builtin_complex(args: &[Value]) -> Result<Value> {
+    if args.len() > 2 {
+        return Err(KyaroError::runtime_error("complex() takes 0-2 arguments".to_string(), 0, 0));
+    }
+    
+    // Omitted parts are zero; either part may itself be complex, as in Python
+    let part = |index: usize| match args.get(index) {
+        None | Some(Value::Null) => Ok(Complex64::zero()),
+        Some(value) => value.as_complex().ok_or_else(|| {
+            KyaroError::runtime_error(format!("complex() requires numbers, not {}", value.type_name()), 0, 0)
+        }),
+    };
+    let (real, imag) = (part(0)?, part(1)?);
+    Ok(Value::Complex(Complex64::new(real.re - imag.im, real.im + imag.re)))
+}
+
+fn builtin_real(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(KyaroError::runtime_error("real() takes exactly one argument".to_string(), 0, 0));
+    }
+    
+    match &args[0] {
+        Value::Complex(n) => Ok(Value::Float(n.re)),
+        Value::Int(_) | Value::Float(_) => Ok(args[0].clone()),
+        _ => Err(KyaroError::runtime_error("real() requires a number".to_string(), 0, 0)),
+    }
+}
+
+fn builtin_imag(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(KyaroError::runtime_error("imag() takes exactly one argument".to_string(), 0, 0));
+    }
+    
+    match &args[0] {
+        Value::Complex(n) => Ok(Value::Float(n.im)),
+        Value::Int(_) => Ok(Value::int(0)),
+        Value::Float(_) => Ok(Value::Float(0.0)),
+        _ => Err(KyaroError::runtime_error("imag() requires a number".to_string(), 0, 0)),
+    }
+}
+
+fn builtin_conj(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(KyaroError::runtime_error("conj() takes exactly one argument".to_string(), 0, 0));
+    }
+    
+    match &args[0] {
+        Value::Complex(n) => Ok(Value::Complex(n.conj())),
+        Value::Int(_) | Value::Float(_) => Ok(args[0].clone()),
+        _ => Err(KyaroError::runtime_error("conj() requires a number".to_string(), 0, 0)),
+    }
+}
+
+/// The angle of a number in the complex plane, in radians.
+fn builtin_phase(args: &[Value]) -> Result<Value> {
+    if args.len() != 1 {
+        return Err(KyaroError::runtime_error("phase() takes exactly one argument".to_string(), 0, 0));
+    }
+    
+    if let Some(n) = args[0].as_complex() {
+        Ok(Value::Float(n.arg()))
     } else {
-        Err(KyaroError::runtime_error("exp() requires a number".to_string(), 0, 0))
+        Err(KyaroError::runtime_error("phase() requires a number".to_string(), 0, 0))
     }
 }
 
+/// `a / b` for complex numbers, scaled like Python's division so that
+/// dividing by a real number divides each part exactly. `b` must not be zero.
+pub fn complex_divide(a: Complex64, b: Complex64) -> Complex64 {
+    if b.re.abs() >= b.im.abs() {
+        let ratio = b.im / b.re;
+        let denominator = b.re + b.im * ratio;
+        Complex64::new((a.re + a.im * ratio) / denominator, (a.im - a.re * ratio) / denominator)
+    } else {
+        let ratio = b.re / b.im;
+        let denominator = b.re * ratio + b.im;
+        Complex64::new((a.re * ratio + a.im) / denominator, (a.im * ratio - a.re) / denominator)
+    }
+}
+
+/// `a ** b` for complex numbers, computed as Python does. Small integral
+/// exponents multiply out exactly, so `(1+1j) ** 2` is `2j`.
+pub fn complex_pow(a: Complex64, b: Complex64) -> Result<Complex64> {
+    if b.is_zero() {
+        return Ok(Complex64::new(1.0, 0.0));
+    }
+    if a.is_zero() {
+        return if b.im != 0.0 || b.re < 0.0 {
+            Err(KyaroError::runtime_error("Zero cannot be raised to a negative or complex power", 0, 0))
+        } else {
+            Ok(Complex64::zero())
+        };
+    }
+    
+    if b.im == 0.0 && b.re.fract() == 0.0 && b.re.abs() <= 100.0 {
+        let power = a.powu(b.re.abs() as u32);
+        return Ok(if b.re < 0.0 { complex_divide(Complex64::new(1.0, 0.0), power) } else { power });
+    }
+    
+    let (magnitude, angle) = (a.norm(), a.arg());
+    let mut length = magnitude.powf(b.re);
+    let mut phase = angle * b.re;
+    if b.im != 0.0 {
+        length /= (angle * b.im).exp();
+        phase += b.im * magnitude.ln();
+    }
+    Ok(Complex64::from_polar(length, phase))
+}
+
 // Trigonometric functions
 fn builtin_sin(args: &[Value]) -> Result<Value> {
     if args.len() != 1 {
         return Err(KyaroError::runtime_error("sin() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        Ok(Value::Number(n.sin()))
+    if let Some(n) = args[0].as_f64() {
+        Ok(Value::Float(n.sin()))
     } else {
         Err(KyaroError::runtime_error("sin() requires a number".to_string(), 0, 0))
     }
@@ -422,8 +1429,8 @@ fn builtin_cos(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("cos() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        Ok(Value::Number(n.cos()))
+    if let Some(n) = args[0].as_f64() {
+        Ok(Value::Float(n.cos()))
     } else {
         Err(KyaroError::runtime_error("cos() requires a number".to_string(), 0, 0))
     }
@@ -434,8 +1441,8 @@ fn builtin_tan(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("tan() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        Ok(Value::Number(n.tan()))
+    if let Some(n) = args[0].as_f64() {
+        Ok(Value::Float(n.tan()))
     } else {
         Err(KyaroError::runtime_error("tan() requires a number".to_string(), 0, 0))
     }
@@ -445,8 +1452,8 @@ fn builtin_tan(args: &[Value]) -> Result<Value> {
 fn builtin_exit(args: &[Value]) -> Result<Value> {
     let code = if args.is_empty() {
         0
-    } else if let Value::Number(n) = &args[0] {
-        *n as i32
+    } else if let Some(n) = integer_value(&args[0]) {
+        n as i32
     } else {
         0
     };
@@ -463,7 +1470,7 @@ fn builtin_time(args: &[Value]) -> Result<Value> {
     let duration = SystemTime::now().duration_since(UNIX_EPOCH)
         .map_err(|_| KyaroError::runtime_error("Time error".to_string(), 0, 0))?;
     
-    Ok(Value::Number(duration.as_secs_f64()))
+    Ok(Value::Float(duration.as_secs_f64()))
 }
 
 fn builtin_sleep(args: &[Value]) -> Result<Value> {
@@ -471,8 +1478,8 @@ fn builtin_sleep(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("sleep() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        std::thread::sleep(std::time::Duration::from_secs_f64(*n));
+    if let Some(n) = args[0].as_f64() {
+        std::thread::sleep(std::time::Duration::from_secs_f64(n));
         Ok(Value::Null)
     } else {
         Err(KyaroError::runtime_error("sleep() requires a number".to_string(), 0, 0))
@@ -480,24 +1487,34 @@ fn builtin_sleep(args: &[Value]) -> Result<Value> {
 }
 
 // Logarithmic functions
+/// Negative and complex arguments give a complex logarithm.
 fn builtin_log(args: &[Value]) -> Result<Value> {
+    let real = |value: &Value| value.as_f64().filter(|n| *n > 0.0);
+    
     match args.len() {
         1 => {
-            if let Value::Number(n) = &args[0] {
-                if *n <= 0.0 {
-                    return Err(KyaroError::runtime_error("log() of non-positive number".to_string(), 0, 0));
+            if let Some(n) = real(&args[0]) {
+                Ok(Value::Float(n.ln()))
+            } else if let Some(n) = args[0].as_complex() {
+                if n.is_zero() {
+                    return Err(KyaroError::runtime_error("log() of zero".to_string(), 0, 0));
                 }
-                Ok(Value::Number(n.ln()))
+                Ok(Value::Complex(n.ln()))
             } else {
                 Err(KyaroError::runtime_error("log() requires a number".to_string(), 0, 0))
             }
         }
         2 => {
-            if let (Value::Number(n), Value::Number(base)) = (&args[0], &args[1]) {
-                if *n <= 0.0 || *base <= 0.0 || *base == 1.0 {
+            if let (Some(n), Some(base)) = (real(&args[0]), real(&args[1])) {
+                if base == 1.0 {
                     return Err(KyaroError::runtime_error("Invalid log arguments".to_string(), 0, 0));
                 }
-                Ok(Value::Number(n.ln() / base.ln()))
+                Ok(Value::Float(n.ln() / base.ln()))
+            } else if let (Some(n), Some(base)) = (args[0].as_complex(), args[1].as_complex()) {
+                if n.is_zero() || base.is_zero() || base == Complex64::new(1.0, 0.0) {
+                    return Err(KyaroError::runtime_error("Invalid log arguments".to_string(), 0, 0));
+                }
+                Ok(Value::Complex(complex_divide(n.ln(), base.ln())))
             } else {
                 Err(KyaroError::runtime_error("log() requires numbers".to_string(), 0, 0))
             }
@@ -511,11 +1528,11 @@ fn builtin_log10(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("log10() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        if *n <= 0.0 {
+    if let Some(n) = args[0].as_f64() {
+        if n <= 0.0 {
             return Err(KyaroError::runtime_error("log10() of non-positive number".to_string(), 0, 0));
         }
-        Ok(Value::Number(n.log10()))
+        Ok(Value::Float(n.log10()))
     } else {
         Err(KyaroError::runtime_error("log10() requires a number".to_string(), 0, 0))
     }
@@ -526,11 +1543,11 @@ fn builtin_log2(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("log2() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        if *n <= 0.0 {
+    if let Some(n) = args[0].as_f64() {
+        if n <= 0.0 {
             return Err(KyaroError::runtime_error("log2() of non-positive number".to_string(), 0, 0));
         }
-        Ok(Value::Number(n.log2()))
+        Ok(Value::Float(n.log2()))
     } else {
         Err(KyaroError::runtime_error("log2() requires a number".to_string(), 0, 0))
     }
@@ -541,11 +1558,11 @@ fn builtin_ln(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("ln() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        if *n <= 0.0 {
+    if let Some(n) = args[0].as_f64() {
+        if n <= 0.0 {
             return Err(KyaroError::runtime_error("ln() of non-positive number".to_string(), 0, 0));
         }
-        Ok(Value::Number(n.ln()))
+        Ok(Value::Float(n.ln()))
     } else {
         Err(KyaroError::runtime_error("ln() requires a number".to_string(), 0, 0))
     }
@@ -557,10 +1574,10 @@ fn builtin_floor(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("floor() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        Ok(Value::Number(n.floor()))
-    } else {
-        Err(KyaroError::runtime_error("floor() requires a number".to_string(), 0, 0))
+    match &args[0] {
+        Value::Int(n) => Ok(Value::Int(n.clone())),
+        Value::Float(n) => float_to_int(n.floor()),
+        _ => Err(KyaroError::runtime_error("floor() requires a number".to_string(), 0, 0)),
     }
 }
 
@@ -569,30 +1586,45 @@ fn builtin_ceil(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("ceil() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        Ok(Value::Number(n.ceil()))
-    } else {
-        Err(KyaroError::runtime_error("ceil() requires a number".to_string(), 0, 0))
+    match &args[0] {
+        Value::Int(n) => Ok(Value::Int(n.clone())),
+        Value::Float(n) => float_to_int(n.ceil()),
+        _ => Err(KyaroError::runtime_error("ceil() requires a number".to_string(), 0, 0)),
     }
 }
 
 fn builtin_round(args: &[Value]) -> Result<Value> {
     match args.len() {
-        1 => {
-            if let Value::Number(n) = &args[0] {
-                Ok(Value::Number(n.round()))
-            } else {
-                Err(KyaroError::runtime_error("round() requires a number".to_string(), 0, 0))
-            }
-        }
-        2 => {
-            if let (Value::Number(n), Value::Number(digits)) = (&args[0], &args[1]) {
-                let factor = 10.0_f64.powf(*digits);
-                Ok(Value::Number((n * factor).round() / factor))
-            } else {
-                Err(KyaroError::runtime_error("round() requires numbers".to_string(), 0, 0))
+        // Halves round to the even neighbour, as in Python
+        1 => match &args[0] {
+            Value::Int(n) => Ok(Value::Int(n.clone())),
+            Value::Float(n) => float_to_int(n.round_ties_even()),
+            _ => Err(KyaroError::runtime_error("round() requires a number".to_string(), 0, 0)),
+        },
+        2 => match (&args[0], &args[1]) {
+            (Value::Int(n), Value::Int(digits)) => match (-digits).to_u32() {
+                Some(places) if places > 0 => {
+                    let factor = BigInt::from(10u32).pow(places);
+                    let (quotient, remainder) = n.div_mod_floor(&factor);
+                    let twice: BigInt = remainder * 2;
+                    let round_up = twice > factor || (twice == factor && quotient.is_odd());
+                    Ok(Value::Int((quotient + u32::from(round_up)) * factor))
+                }
+                _ => Ok(Value::Int(n.clone())),
+            },
+            (Value::Float(n), Value::Int(digits)) => {
+                // A float has no decimal digits past the 1074th place
+                let digits = digits.to_i32().unwrap_or(if digits.is_negative() { i32::MIN } else { i32::MAX }).min(1100);
+                if digits >= 0 {
+                    // Rounding the exact decimal expansion keeps `2.675` from rounding up
+                    Ok(Value::Float(format!("{:.*}", digits as usize, n).parse().unwrap_or(*n)))
+                } else {
+                    let factor = 10.0_f64.powi(-digits.max(-308));
+                    Ok(Value::Float((n / factor).round_ties_even() * factor))
+                }
             }
-        }
+            _ => Err(KyaroError::runtime_error("round() requires a number and an integer".to_string(), 0, 0)),
+        },
         _ => Err(KyaroError::runtime_error("round() takes 1 or 2 arguments".to_string(), 0, 0)),
     }
 }
@@ -602,10 +1634,10 @@ fn builtin_trunc(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("trunc() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        Ok(Value::Number(n.trunc()))
-    } else {
-        Err(KyaroError::runtime_error("trunc() requires a number".to_string(), 0, 0))
+    match &args[0] {
+        Value::Int(n) => Ok(Value::Int(n.clone())),
+        Value::Float(n) => float_to_int(n.trunc()),
+        _ => Err(KyaroError::runtime_error("trunc() requires a number".to_string(), 0, 0)),
     }
 }
 
@@ -615,11 +1647,11 @@ fn builtin_asin(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("asin() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        if *n < -1.0 || *n > 1.0 {
+    if let Some(n) = args[0].as_f64() {
+        if !(-1.0..=1.0).contains(&n) {
             return Err(KyaroError::runtime_error("asin() domain error".to_string(), 0, 0));
         }
-        Ok(Value::Number(n.asin()))
+        Ok(Value::Float(n.asin()))
     } else {
         Err(KyaroError::runtime_error("asin() requires a number".to_string(), 0, 0))
     }
@@ -630,11 +1662,11 @@ fn builtin_acos(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("acos() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        if *n < -1.0 || *n > 1.0 {
+    if let Some(n) = args[0].as_f64() {
+        if !(-1.0..=1.0).contains(&n) {
             return Err(KyaroError::runtime_error("acos() domain error".to_string(), 0, 0));
         }
-        Ok(Value::Number(n.acos()))
+        Ok(Value::Float(n.acos()))
     } else {
         Err(KyaroError::runtime_error("acos() requires a number".to_string(), 0, 0))
     }
@@ -645,8 +1677,8 @@ fn builtin_atan(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("atan() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        Ok(Value::Number(n.atan()))
+    if let Some(n) = args[0].as_f64() {
+        Ok(Value::Float(n.atan()))
     } else {
         Err(KyaroError::runtime_error("atan() requires a number".to_string(), 0, 0))
     }
@@ -657,8 +1689,8 @@ fn builtin_atan2(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("atan2() takes exactly two arguments".to_string(), 0, 0));
     }
     
-    if let (Value::Number(y), Value::Number(x)) = (&args[0], &args[1]) {
-        Ok(Value::Number(y.atan2(*x)))
+    if let (Some(y), Some(x)) = (args[0].as_f64(), args[1].as_f64()) {
+        Ok(Value::Float(y.atan2(x)))
     } else {
         Err(KyaroError::runtime_error("atan2() requires numbers".to_string(), 0, 0))
     }
@@ -670,8 +1702,8 @@ fn builtin_sinh(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("sinh() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        Ok(Value::Number(n.sinh()))
+    if let Some(n) = args[0].as_f64() {
+        Ok(Value::Float(n.sinh()))
     } else {
         Err(KyaroError::runtime_error("sinh() requires a number".to_string(), 0, 0))
     }
@@ -682,8 +1714,8 @@ fn builtin_cosh(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("cosh() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        Ok(Value::Number(n.cosh()))
+    if let Some(n) = args[0].as_f64() {
+        Ok(Value::Float(n.cosh()))
     } else {
         Err(KyaroError::runtime_error("cosh() requires a number".to_string(), 0, 0))
     }
@@ -694,8 +1726,8 @@ fn builtin_tanh(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("tanh() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        Ok(Value::Number(n.tanh()))
+    if let Some(n) = args[0].as_f64() {
+        Ok(Value::Float(n.tanh()))
     } else {
         Err(KyaroError::runtime_error("tanh() requires a number".to_string(), 0, 0))
     }
@@ -706,8 +1738,8 @@ fn builtin_asinh(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("asinh() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        Ok(Value::Number(n.asinh()))
+    if let Some(n) = args[0].as_f64() {
+        Ok(Value::Float(n.asinh()))
     } else {
         Err(KyaroError::runtime_error("asinh() requires a number".to_string(), 0, 0))
     }
@@ -718,11 +1750,11 @@ fn builtin_acosh(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("acosh() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        if *n < 1.0 {
+    if let Some(n) = args[0].as_f64() {
+        if n < 1.0 {
             return Err(KyaroError::runtime_error("acosh() domain error".to_string(), 0, 0));
         }
-        Ok(Value::Number(n.acosh()))
+        Ok(Value::Float(n.acosh()))
     } else {
         Err(KyaroError::runtime_error("acosh() requires a number".to_string(), 0, 0))
     }
@@ -733,11 +1765,11 @@ fn builtin_atanh(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("atanh() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        if *n <= -1.0 || *n >= 1.0 {
+    if let Some(n) = args[0].as_f64() {
+        if n <= -1.0 || n >= 1.0 {
             return Err(KyaroError::runtime_error("atanh() domain error".to_string(), 0, 0));
         }
-        Ok(Value::Number(n.atanh()))
+        Ok(Value::Float(n.atanh()))
     } else {
         Err(KyaroError::runtime_error("atanh() requires a number".to_string(), 0, 0))
     }
@@ -749,8 +1781,8 @@ fn builtin_degrees(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("degrees() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        Ok(Value::Number(n.to_degrees()))
+    if let Some(n) = args[0].as_f64() {
+        Ok(Value::Float(n.to_degrees()))
     } else {
         Err(KyaroError::runtime_error("degrees() requires a number".to_string(), 0, 0))
     }
@@ -761,8 +1793,8 @@ fn builtin_radians(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("radians() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        Ok(Value::Number(n.to_radians()))
+    if let Some(n) = args[0].as_f64() {
+        Ok(Value::Float(n.to_radians()))
     } else {
         Err(KyaroError::runtime_error("radians() requires a number".to_string(), 0, 0))
     }
@@ -776,14 +1808,14 @@ fn builtin_hypot(args: &[Value]) -> Result<Value> {
     
     let mut sum_squares = 0.0;
     for arg in args {
-        if let Value::Number(n) = arg {
+        if let Some(n) = arg.as_f64() {
             sum_squares += n * n;
         } else {
             return Err(KyaroError::runtime_error("hypot() requires numbers".to_string(), 0, 0));
         }
     }
     
-    Ok(Value::Number(sum_squares.sqrt()))
+    Ok(Value::Float(sum_squares.sqrt()))
 }
 
 fn builtin_factorial(args: &[Value]) -> Result<Value> {
@@ -791,20 +1823,14 @@ fn builtin_factorial(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("factorial() takes exactly one argument".to_string(), 0, 0));
     }
     
-    if let Value::Number(n) = &args[0] {
-        let n_int = *n as i64;
-        if n_int < 0 || (*n - n_int as f64).abs() > f64::EPSILON {
-            return Err(KyaroError::runtime_error("factorial() requires a non-negative integer".to_string(), 0, 0));
-        }
-        
-        let mut result = 1.0;
-        for i in 1..=n_int {
-            result *= i as f64;
+    match &args[0] {
+        Value::Int(n) if !n.is_negative() => {
+            let n = n.to_u64().ok_or_else(|| {
+                KyaroError::runtime_error(format!("factorial() argument {} is too large", n), 0, 0)
+            })?;
+            Ok(Value::Int((1..=n).fold(BigInt::from(1u32), |product, i| product * i)))
         }
-        
-        Ok(Value::Number(result))
-    } else {
-        Err(KyaroError::runtime_error("factorial() requires a number".to_string(), 0, 0))
+        _ => Err(KyaroError::runtime_error("factorial() requires a non-negative integer".to_string(), 0, 0)),
     }
 }
 
@@ -813,30 +1839,16 @@ fn builtin_gcd(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("gcd() requires at least 2 arguments".to_string(), 0, 0));
     }
     
-    let mut result = if let Value::Number(n) = &args[0] {
-        (*n as i64).abs()
-    } else {
-        return Err(KyaroError::runtime_error("gcd() requires integers".to_string(), 0, 0));
-    };
-    
-    for arg in args.iter().skip(1) {
-        if let Value::Number(n) = arg {
-            let n_int = (*n as i64).abs();
-            result = gcd_helper(result, n_int);
+    let mut result = BigInt::zero();
+    for arg in args {
+        if let Value::Int(n) = arg {
+            result = result.gcd(n);
         } else {
             return Err(KyaroError::runtime_error("gcd() requires integers".to_string(), 0, 0));
         }
     }
     
-    Ok(Value::Number(result as f64))
-}
-
-fn gcd_helper(a: i64, b: i64) -> i64 {
-    if b == 0 {
-        a
-    } else {
-        gcd_helper(b, a % b)
-    }
+    Ok(Value::Int(result))
 }
 
 // Random functions
@@ -846,7 +1858,7 @@ fn builtin_random(args: &[Value]) -> Result<Value> {
     }
     
     let mut rng = thread_rng();
-    Ok(Value::Number(rng.gen::<f64>()))
+    Ok(Value::Float(rng.gen::<f64>()))
 }
 
 fn builtin_randint(args: &[Value]) -> Result<Value> {
@@ -854,11 +1866,9 @@ fn builtin_randint(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("randint() takes exactly two arguments".to_string(), 0, 0));
     }
     
-    if let (Value::Number(a), Value::Number(b)) = (&args[0], &args[1]) {
-        let min = (*a as i64).min(*b as i64);
-        let max = (*a as i64).max(*b as i64);
+    if let (Some(a), Some(b)) = (integer_value(&args[0]), integer_value(&args[1])) {
         let mut rng = thread_rng();
-        Ok(Value::Number(rng.gen_range(min..=max) as f64))
+        Ok(Value::int(rng.gen_range(a.min(b)..=a.max(b))))
     } else {
         Err(KyaroError::runtime_error("randint() requires integers".to_string(), 0, 0))
     }
@@ -869,9 +1879,9 @@ fn builtin_uniform(args: &[Value]) -> Result<Value> {
         return Err(KyaroError::runtime_error("uniform() takes exactly two arguments".to_string(), 0, 0));
     }
     
-    if let (Value::Number(a), Value::Number(b)) = (&args[0], &args[1]) {
+    if let (Some(a), Some(b)) = (args[0].as_f64(), args[1].as_f64()) {
         let mut rng = thread_rng();
-        Ok(Value::Number(rng.gen_range(*a..*b)))
+        Ok(Value::Float(rng.gen_range(a..b)))
     } else {
         Err(KyaroError::runtime_error("uniform() requires numbers".to_string(), 0, 0))
     }
@@ -883,6 +1893,7 @@ fn builtin_choice(args: &[Value]) -> Result<Value> {
     }
     
     if let Value::List(list) = &args[0] {
+        let list = list.borrow();
         if list.is_empty() {
             return Err(KyaroError::runtime_error("choice() from empty list".to_string(), 0, 0));
         }
@@ -902,20 +1913,31 @@ fn builtin_mean(args: &[Value]) -> Result<Value> {
     }
     
     if let Value::List(list) = &args[0] {
+        let list = list.borrow();
         if list.is_empty() {
             return Err(KyaroError::runtime_error("mean() of empty list".to_string(), 0, 0));
         }
         
+        // As in Python, the mean of integers stays an integer when it is exact
+        let integers: Option<Vec<&BigInt>> = list.iter().map(|val| if let Value::Int(n) = val { Some(n) } else { None }).collect();
+        if let Some(integers) = integers {
+            let sum: BigInt = integers.into_iter().sum();
+            let (quotient, remainder) = sum.div_rem(&BigInt::from(list.len()));
+            if remainder.is_zero() {
+                return Ok(Value::Int(quotient));
+            }
+        }
+        
         let mut sum = 0.0;
-        for val in list {
-            if let Value::Number(n) = val {
+        for val in list.iter() {
+            if let Some(n) = val.as_f64() {
                 sum += n;
             } else {
                 return Err(KyaroError::runtime_error("mean() requires a list of numbers".to_string(), 0, 0));
             }
         }
         
-        Ok(Value::Number(sum / list.len() as f64))
+        Ok(Value::Float(sum / list.len() as f64))
     } else {
         Err(KyaroError::runtime_error("mean() requires a list".to_string(), 0, 0))
     }
@@ -927,26 +1949,28 @@ fn builtin_median(args: &[Value]) -> Result<Value> {
     }
     
     if let Value::List(list) = &args[0] {
+        let list = list.borrow();
         if list.is_empty() {
             return Err(KyaroError::runtime_error("median() of empty list".to_string(), 0, 0));
         }
         
         let mut numbers = Vec::new();
-        for val in list {
-            if let Value::Number(n) = val {
-                numbers.push(*n);
+        for val in list.iter() {
+            if let Some(n) = val.as_f64() {
+                numbers.push((n, val));
             } else {
                 return Err(KyaroError::runtime_error("median() requires a list of numbers".to_string(), 0, 0));
             }
         }
         
-        numbers.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        numbers.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
         let len = numbers.len();
         
+        // An odd-length list's median is its middle element, unconverted
         if len % 2 == 0 {
-            Ok(Value::Number((numbers[len / 2 - 1] + numbers[len / 2]) / 2.0))
+            Ok(Value::Float((numbers[len / 2 - 1].0 + numbers[len / 2].0) / 2.0))
         } else {
-            Ok(Value::Number(numbers[len / 2]))
+            Ok(numbers[len / 2].1.clone())
         }
     } else {
         Err(KyaroError::runtime_error("median() requires a list".to_string(), 0, 0))
@@ -959,14 +1983,15 @@ fn builtin_stdev(args: &[Value]) -> Result<Value> {
     }
     
     if let Value::List(list) = &args[0] {
+        let list = list.borrow();
         if list.len() < 2 {
             return Err(KyaroError::runtime_error("stdev() requires at least 2 values".to_string(), 0, 0));
         }
         
         let mut numbers = Vec::new();
-        for val in list {
-            if let Value::Number(n) = val {
-                numbers.push(*n);
+        for val in list.iter() {
+            if let Some(n) = val.as_f64() {
+                numbers.push(n);
             } else {
                 return Err(KyaroError::runtime_error("stdev() requires a list of numbers".to_string(), 0, 0));
             }
@@ -977,7 +2002,7 @@ fn builtin_stdev(args: &[Value]) -> Result<Value> {
             .map(|x| (x - mean).powi(2))
             .sum::<f64>() / (numbers.len() - 1) as f64;
         
-        Ok(Value::Number(variance.sqrt()))
+        Ok(Value::Float(variance.sqrt()))
     } else {
         Err(KyaroError::runtime_error("stdev() requires a list".to_string(), 0, 0))
     }
@@ -989,14 +2014,15 @@ fn builtin_variance(args: &[Value]) -> Result<Value> {
     }
     
     if let Value::List(list) = &args[0] {
+        let list = list.borrow();
         if list.len() < 2 {
             return Err(KyaroError::runtime_error("variance() requires at least 2 values".to_string(), 0, 0));
         }
         
         let mut numbers = Vec::new();
-        for val in list {
-            if let Value::Number(n) = val {
-                numbers.push(*n);
+        for val in list.iter() {
+            if let Some(n) = val.as_f64() {
+                numbers.push(n);
             } else {
                 return Err(KyaroError::runtime_error("variance() requires a list of numbers".to_string(), 0, 0));
             }
@@ -1007,7 +2033,7 @@ fn builtin_variance(args: &[Value]) -> Result<Value> {
             .map(|x| (x - mean).powi(2))
             .sum::<f64>() / (numbers.len() - 1) as f64;
         
-        Ok(Value::Number(variance))
+        Ok(Value::Float(variance))
     } else {
         Err(KyaroError::runtime_error("variance() requires a list".to_string(), 0, 0))
     }
@@ -1018,12 +2044,125 @@ fn builtin_pi(args: &[Value]) -> Result<Value> {
     if !args.is_empty() {
         return Err(KyaroError::runtime_error("pi() takes no arguments".to_string(), 0, 0));
     }
-    Ok(Value::Number(std::f64::consts::PI))
+    Ok(Value::Float(std::f64::consts::PI))
 }
 
 fn builtin_e(args: &[Value]) -> Result<Value> {
     if !args.is_empty() {
         return Err(KyaroError::runtime_error("e() takes no arguments".to_string(), 0, 0));
     }
-    Ok(Value::Number(std::f64::consts::E))
+    Ok(Value::Float(std::f64::consts::E))
+}
+
+// Higher-order functions. Each works on a snapshot of the list, since the
+// functions they call may modify it.
+fn builtin_map(context: &mut dyn NativeContext, args: &[Value]) -> Result<Value> {
+    if args.len() != 2 {
+        return Err(KyaroError::runtime_error("map() takes exactly two arguments".to_string(), 0, 0));
+    }
+    
+    let items = list_arg(&args[1], "map")?.borrow().clone();
+    let mut results = Vec::with_capacity(items.len());
+    for item in items {
+        results.push(context.call(&args[0], vec![item])?);
+    }
+    Ok(Value::list(results))
+}
+
+fn builtin_filter(context: &mut dyn NativeContext, args: &[Value]) -> Result<Value> {
+    if args.len() != 2 {
+        return Err(KyaroError::runtime_error("filter() takes exactly two arguments".to_string(), 0, 0));
+    }
+    
+    let items = list_arg(&args[1], "filter")?.borrow().clone();
+    let mut results = Vec::new();
+    for item in items {
+        if context.call(&args[0], vec![item.clone()])?.is_truthy() {
+            results.push(item);
+        }
+    }
+    Ok(Value::list(results))
+}
+
+fn builtin_reduce(context: &mut dyn NativeContext, args: &[Value]) -> Result<Value> {
+    if args.len() < 2 || args.len() > 3 {
+        return Err(KyaroError::runtime_error("reduce() takes 2-3 arguments".to_string(), 0, 0));
+    }
+    
+    let mut items = list_arg(&args[1], "reduce")?.borrow().clone().into_iter();
+    let mut accumulator = match args.get(2) {
+        Some(initial) => initial.clone(),
+        None => items.next().ok_or_else(|| {
+            KyaroError::runtime_error("reduce() of empty list with no initial value".to_string(), 0, 0)
+        })?,
+    };
+    
+    for item in items {
+        accumulator = context.call(&args[0], vec![accumulator, item])?;
+    }
+    Ok(accumulator)
+}
+
+fn builtin_sorted(context: &mut dyn NativeContext, args: &[Value]) -> Result<Value> {
+    if args.is_empty() || args.len() > 2 {
+        return Err(KyaroError::runtime_error("sorted() takes 1-2 arguments".to_string(), 0, 0));
+    }
+    
+    let items = list_arg(&args[0], "sorted")?.borrow().clone();
+    let key = args.get(1).filter(|key| !matches!(key, Value::Null));
+    
+    // Keys are computed once per item, before sorting
+    let mut keyed = Vec::with_capacity(items.len());
+    for item in items {
+        let sort_key = match key {
+            Some(key) => context.call(key, vec![item.clone()])?,
+            None => item.clone(),
+        };
+        keyed.push((sort_key, item));
+    }
+    
+    sort_by_value(&mut keyed, |(sort_key, _)| sort_key)?;
+    Ok(Value::list(keyed.into_iter().map(|(_, item)| item).collect()))
+}
+
+/// The items and optional predicate of `all`/`any`: `(list)`, or
+/// `(predicate, list)` like `map` and `filter`. The older `(list, predicate)`
+/// order is still accepted.
+fn truth_test_args<'a>(args: &'a [Value], name: &str) -> Result<(Vec<Value>, Option<&'a Value>)> {
+    let (predicate, list) = match args {
+        [list] => (None, list),
+        [list @ Value::List(_), predicate] | [predicate, list] => (Some(predicate), list),
+        _ => return Err(KyaroError::runtime_error(format!("{}() takes 1-2 arguments", name), 0, 0)),
+    };
+    
+    let items = list_arg(list, name)?.borrow().clone();
+    Ok((items, predicate.filter(|predicate| !matches!(predicate, Value::Null))))
+}
+
+fn builtin_all(context: &mut dyn NativeContext, args: &[Value]) -> Result<Value> {
+    let (items, predicate) = truth_test_args(args, "all")?;
+    for item in items {
+        let passed = match predicate {
+            Some(predicate) => context.call(predicate, vec![item])?.is_truthy(),
+            None => item.is_truthy(),
+        };
+        if !passed {
+            return Ok(Value::Boolean(false));
+        }
+    }
+    Ok(Value::Boolean(true))
+}
+
+fn builtin_any(context: &mut dyn NativeContext, args: &[Value]) -> Result<Value> {
+    let (items, predicate) = truth_test_args(args, "any")?;
+    for item in items {
+        let passed = match predicate {
+            Some(predicate) => context.call(predicate, vec![item])?.is_truthy(),
+            None => item.is_truthy(),
+        };
+        if passed {
+            return Ok(Value::Boolean(true));
+        }
+    }
+    Ok(Value::Boolean(false))
 }
@@ -1,29 +1,301 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use image::DynamicImage;
+use indexmap::IndexMap;
+use num_bigint::BigInt;
+use num_complex::Complex64;
+use num_traits::{FromPrimitive, ToPrimitive, Zero};
+use crate::builtins::NativeFn;
 use crate::errors::{KyaroError, Result};
+use crate::image_functions::mode_name;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub enum Value {
-    Number(f64),
+    Int(BigInt),
+    Float(f64),
+    Complex(Complex64),
     String(String),
     Boolean(bool),
     Null,
-    List(Vec<Value>),
+    List(ListRef),
+    Dict(DictRef),
     Function {
         name: String,
-        parameters: Vec<String>,
-        body: crate::ast_nodes::ASTNode,
+        parameters: Rc<Parameters>,
+        body: Rc<crate::ast_nodes::ASTNode>,
+        closure: EnvRef,
+        /// The module file the function was defined in, `None` for the main program.
+        file: Option<Rc<str>>,
     },
+    NativeFunction {
+        name: String,
+        function: NativeFn,
+    },
+    Class(Rc<Class>),
+    Instance(InstanceRef),
+    Module(Rc<Module>),
+    /// A raster image from the `image` module, shared like lists so that
+    /// in-place operations are visible through every reference.
+    Image(ImageRef),
+    /// A method looked up on a value, called with the value as its first
+    /// argument: an instance's method gets the instance as `self`, and a
+    /// string method gets the string.
+    BoundMethod {
+        receiver: Box<Value>,
+        method: Box<Value>,
+    },
+}
+
+/// A function's parameters, with default values evaluated when the
+/// function was defined.
+#[derive(Debug)]
+pub struct Parameters {
+    pub names: Vec<String>,
+    pub defaults: Vec<Option<Value>>,
+    /// How many of `names` can be passed positionally; the rest are keyword-only.
+    pub positional: usize,
+    /// Collects extra positional arguments into a list.
+    pub rest: Option<String>,
+    /// Collects unmatched keyword arguments into a dict.
+    pub keywords: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Class {
+    pub name: String,
+    pub superclass: Option<Rc<Class>>,
+    pub methods: HashMap<String, Value>,
+}
+
+impl Class {
+    /// Looks a method up on this class, then along its superclasses.
+    pub fn find_method(&self, name: &str) -> Option<Value> {
+        match self.methods.get(name) {
+            Some(method) => Some(method.clone()),
+            None => self.superclass.as_ref().and_then(|parent| parent.find_method(name)),
+        }
+    }
+    
+    /// Whether `class` is this class or one of its superclasses.
+    pub fn inherits_from(&self, class: &Class) -> bool {
+        std::ptr::eq(self, class) || self.superclass.as_ref().is_some_and(|parent| parent.inherits_from(class))
+    }
+}
+
+/// An imported file. Its top-level bindings are read through member access.
+#[derive(Debug)]
+pub struct Module {
+    pub name: String,
+    pub scope: EnvRef,
+}
+
+pub type InstanceRef = Rc<RefCell<Instance>>;
+
+pub struct Instance {
+    pub class: Rc<Class>,
+    pub fields: HashMap<String, Value>,
+}
+
+impl Instance {
+    pub fn new(class: Rc<Class>) -> Self {
+        Self {
+            class,
+            fields: HashMap::new(),
+        }
+    }
+}
+
+impl fmt::Debug for Instance {
+    // Fields may refer back to the instance, so only the class is shown
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Instance")
+            .field("class", &self.class.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// List storage shared by every value that refers to the same list, so
+/// in-place operations are visible through all of them.
+pub type ListRef = Rc<RefCell<Vec<Value>>>;
+
+/// Dict storage, shared the same way as lists. Entries keep insertion order.
+pub type DictRef = Rc<RefCell<IndexMap<DictKey, Value>>>;
+
+pub type ImageRef = Rc<RefCell<DynamicImage>>;
+
+/// The hashable values that can be used as dict keys. Booleans are not
+/// numbers in Kyaro, so unlike Python `true` and `1` are different keys.
+#[derive(Debug, Clone)]
+pub enum DictKey {
+    Int(BigInt),
+    /// A float's bits, with -0.0 stored as 0.0 since they compare equal.
+    Float(u64),
+    /// The bits of the real and imaginary parts, stored like `Float`.
+    Complex(u64, u64),
+    String(String),
+    Boolean(bool),
+    Null,
+}
+
+impl DictKey {
+    pub fn from_value(value: &Value) -> Result<Self> {
+        match value {
+            Value::Int(n) => Ok(DictKey::Int(n.clone())),
+            Value::Float(n) => Ok(DictKey::Float(key_bits(*n))),
+            Value::Complex(n) => Ok(DictKey::Complex(key_bits(n.re), key_bits(n.im))),
+            Value::String(s) => Ok(DictKey::String(s.clone())),
+            Value::Boolean(b) => Ok(DictKey::Boolean(*b)),
+            Value::Null => Ok(DictKey::Null),
+            other => Err(KyaroError::runtime_error(
+                format!("Unhashable type: {}", other.type_name()),
+                0,
+                0,
+            )),
+        }
+    }
+    
+    pub fn to_value(&self) -> Value {
+        match self {
+            DictKey::Int(n) => Value::Int(n.clone()),
+            DictKey::Float(bits) => Value::Float(f64::from_bits(*bits)),
+            DictKey::Complex(re, im) => Value::Complex(Complex64::new(f64::from_bits(*re), f64::from_bits(*im))),
+            DictKey::String(s) => Value::String(s.clone()),
+            DictKey::Boolean(b) => Value::Boolean(*b),
+            DictKey::Null => Value::Null,
+        }
+    }
+}
+
+fn key_bits(n: f64) -> u64 {
+    if n == 0.0 { 0.0f64 } else { n }.to_bits()
+}
+
+// `1`, `1.0` and `1+0j` are the same key, as in Python
+impl PartialEq for DictKey {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                DictKey::Int(_) | DictKey::Float(_) | DictKey::Complex(..),
+                DictKey::Int(_) | DictKey::Float(_) | DictKey::Complex(..),
+            ) => self.to_value() == other.to_value(),
+            (DictKey::String(a), DictKey::String(b)) => a == b,
+            (DictKey::Boolean(a), DictKey::Boolean(b)) => a == b,
+            (DictKey::Null, DictKey::Null) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for DictKey {}
+
+impl Hash for DictKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            DictKey::Int(n) => n.hash(state),
+            DictKey::Float(bits) => hash_float(*bits, state),
+            // Complex numbers with no imaginary part hash as their real part
+            DictKey::Complex(re, im) if f64::from_bits(*im) == 0.0 => hash_float(*re, state),
+            DictKey::Complex(re, im) => (re, im).hash(state),
+            DictKey::String(s) => s.hash(state),
+            DictKey::Boolean(b) => b.hash(state),
+            DictKey::Null => {}
+        }
+    }
+}
+
+/// Integral floats hash as the integer they equal.
+fn hash_float<H: Hasher>(bits: u64, state: &mut H) {
+    let n = f64::from_bits(bits);
+    match BigInt::from_f64(n) {
+        Some(int) if n.fract() == 0.0 => int.hash(state),
+        _ => bits.hash(state),
+    }
 }
 
 impl Value {
+    pub fn int(n: impl Into<BigInt>) -> Self {
+        Value::Int(n.into())
+    }
+    
+    pub fn list(elements: Vec<Value>) -> Self {
+        Value::List(Rc::new(RefCell::new(elements)))
+    }
+    
+    pub fn dict(entries: IndexMap<DictKey, Value>) -> Self {
+        Value::Dict(Rc::new(RefCell::new(entries)))
+    }
+    
     pub fn type_name(&self) -> &'static str {
         match self {
-            Value::Number(_) => "number",
+            Value::Int(_) => "integer",
+            Value::Float(_) => "float",
+            Value::Complex(_) => "complex",
             Value::String(_) => "string",
             Value::Boolean(_) => "boolean",
             Value::Null => "null",
             Value::List(_) => "list",
-            Value::Function { .. } => "function",
+            Value::Dict(_) => "dict",
+            Value::Function { .. } | Value::NativeFunction { .. } => "function",
+            Value::Class(_) => "class",
+            Value::Instance(_) => "object",
+            Value::Module(_) => "module",
+            Value::Image(_) => "image",
+            Value::BoundMethod { .. } => "method",
+        }
+    }
+    
+    /// The value as a float, for ints and floats. Ints too large for a
+    /// float become infinite.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Int(n) => Some(n.to_f64().unwrap_or(f64::NAN)),
+            Value::Float(n) => Some(*n),
+            _ => None,
+        }
+    }
+    
+    /// The value as a complex number, for any number.
+    pub fn as_complex(&self) -> Option<Complex64> {
+        match self {
+            Value::Complex(n) => Some(*n),
+            other => other.as_f64().map(Complex64::from),
+        }
+    }
+    
+    /// Orders numbers numerically and strings and lists lexicographically.
+    /// `symbol` names the operation in the error for unorderable types. NaN
+    /// is unordered with every number, giving `None`, so comparisons with it
+    /// are false as in Python.
+    pub fn compare(&self, other: &Value, symbol: &str) -> Result<Option<Ordering>> {
+        match (self, other) {
+            (Value::Int(_) | Value::Float(_), Value::Int(_) | Value::Float(_)) => Ok(compare_numbers(self, other)),
+            (Value::String(a), Value::String(b)) => Ok(Some(a.cmp(b))),
+            (Value::List(a), Value::List(b)) => {
+                let (a, b) = (a.borrow(), b.borrow());
+                for (x, y) in a.iter().zip(b.iter()) {
+                    if x != y {
+                        return x.compare(y, symbol);
+                    }
+                }
+                Ok(Some(a.len().cmp(&b.len())))
+            }
+            (a, b) => Err(KyaroError::runtime_error(
+                format!("'{}' not supported between {} and {}", symbol, a.type_name(), b.type_name()),
+                0,
+                0,
+            )),
+        }
+    }
+    
+    /// How the value is shown inside a container: strings are quoted.
+    pub fn repr(&self) -> String {
+        match self {
+            Value::String(s) => format!("'{}'", s),
+            other => other.to_string(),
         }
     }
     
@@ -31,41 +303,264 @@ impl Value {
         match self {
             Value::Boolean(b) => *b,
             Value::Null => false,
-            Value::Number(n) => *n != 0.0,
+            Value::Int(n) => !n.is_zero(),
+            Value::Float(n) => *n != 0.0,
+            Value::Complex(n) => !n.is_zero(),
             Value::String(s) => !s.is_empty(),
-            Value::List(l) => !l.is_empty(),
-            Value::Function { .. } => true,
+            Value::List(l) => !l.borrow().is_empty(),
+            Value::Dict(d) => !d.borrow().is_empty(),
+            Value::Function { .. } | Value::NativeFunction { .. } => true,
+            Value::Class(_) | Value::Instance(_) | Value::Module(_) | Value::Image(_) => true,
+            Value::BoundMethod { .. } => true,
         }
     }
-    
-    pub fn to_string(&self) -> String {
-        match self {
-            Value::Number(n) => {
-                if n.fract() == 0.0 {
-                    format!("{}", *n as i64)
-                } else {
-                    format!("{}", n)
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        self.equals(other, &mut Vec::new())
+    }
+}
+
+impl Value {
+    /// Structural equality. `open` holds the pairs of lists and dicts being
+    /// compared further out: meeting one again means the containers contain
+    /// themselves, and the pair is taken as equal instead of recursing forever.
+    fn equals(&self, other: &Value, open: &mut Vec<(*const (), *const ())>) -> bool {
+        match (self, other) {
+            (Value::Int(_) | Value::Float(_), Value::Int(_) | Value::Float(_)) => {
+                compare_numbers(self, other) == Some(Ordering::Equal)
+            }
+            (Value::Complex(a), b) | (b, Value::Complex(a)) => b.as_complex() == Some(*a),
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::Null, Value::Null) => true,
+            (Value::List(a), Value::List(b)) => {
+                let pair = (Rc::as_ptr(a) as *const (), Rc::as_ptr(b) as *const ());
+                if Rc::ptr_eq(a, b) || open.contains(&pair) {
+                    return true;
+                }
+                
+                let (a, b) = (a.borrow(), b.borrow());
+                open.push(pair);
+                let equal = a.len() == b.len() && a.iter().zip(b.iter()).all(|(x, y)| x.equals(y, open));
+                open.pop();
+                equal
+            }
+            (Value::Dict(a), Value::Dict(b)) => {
+                let pair = (Rc::as_ptr(a) as *const (), Rc::as_ptr(b) as *const ());
+                if Rc::ptr_eq(a, b) || open.contains(&pair) {
+                    return true;
                 }
+                
+                // Like Python, dicts are equal regardless of insertion order
+                let (a, b) = (a.borrow(), b.borrow());
+                open.push(pair);
+                let equal = a.len() == b.len()
+                    && a.iter().all(|(key, x)| b.get(key).is_some_and(|y| x.equals(y, open)));
+                open.pop();
+                equal
             }
-            Value::String(s) => s.clone(),
-            Value::Boolean(b) => if *b { "True".to_string() } else { "False".to_string() },
-            Value::Null => "None".to_string(),
+            // Functions are equal only if they are the same definition closing over the same scope
+            (
+                Value::Function { body: a_body, closure: a_env, .. },
+                Value::Function { body: b_body, closure: b_env, .. },
+            ) => Rc::ptr_eq(a_body, b_body) && Rc::ptr_eq(a_env, b_env),
+            // Native functions are identified by their registered name
+            (Value::NativeFunction { name: a, .. }, Value::NativeFunction { name: b, .. }) => a == b,
+            // Classes and instances compare by identity
+            (Value::Class(a), Value::Class(b)) => Rc::ptr_eq(a, b),
+            (Value::Instance(a), Value::Instance(b)) => Rc::ptr_eq(a, b),
+            (Value::Module(a), Value::Module(b)) => Rc::ptr_eq(a, b),
+            (Value::Image(a), Value::Image(b)) => Rc::ptr_eq(a, b),
+            (
+                Value::BoundMethod { receiver: a_receiver, method: a_method },
+                Value::BoundMethod { receiver: b_receiver, method: b_method },
+            ) => a_receiver.equals(b_receiver, open) && a_method.equals(b_method, open),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, &mut Vec::new())
+    }
+}
+
+impl Value {
+    /// Writes the value for display. `open` holds the lists and dicts being
+    /// written further out, so a container that contains itself is shown as
+    /// `[...]` or `{...}`.
+    fn write(&self, f: &mut fmt::Formatter<'_>, open: &mut Vec<*const ()>) -> fmt::Result {
+        match self {
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Float(n) => write!(f, "{}", float_repr(*n)),
+            Value::Complex(n) => write!(f, "{}", complex_repr(*n)),
+            Value::String(s) => write!(f, "{}", s),
+            Value::Boolean(b) => write!(f, "{}", if *b { "True" } else { "False" }),
+            Value::Null => write!(f, "None"),
             Value::List(l) => {
-                let elements: Vec<String> = l.iter().map(|v| match v {
-                    Value::String(s) => format!("'{}'", s),
-                    _ => v.to_string()
-                }).collect();
-                format!("[{}]", elements.join(", "))
+                let id = Rc::as_ptr(l) as *const ();
+                if open.contains(&id) {
+                    return write!(f, "[...]");
+                }
+                
+                open.push(id);
+                write!(f, "[")?;
+                for (i, item) in l.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    item.write_repr(f, open)?;
+                }
+                open.pop();
+                write!(f, "]")
+            }
+            Value::Dict(d) => {
+                let id = Rc::as_ptr(d) as *const ();
+                if open.contains(&id) {
+                    return write!(f, "{{...}}");
+                }
+                
+                open.push(id);
+                write!(f, "{{")?;
+                for (i, (key, value)) in d.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: ", key.to_value().repr())?;
+                    value.write_repr(f, open)?;
+                }
+                open.pop();
+                write!(f, "}}")
+            }
+            Value::Function { name, .. } => write!(f, "<function {}>", name),
+            Value::NativeFunction { name, .. } => write!(f, "<built-in function {}>", name),
+            Value::Class(class) => write!(f, "<class {}>", class.name),
+            Value::Instance(instance) => write!(f, "<{} object>", instance.borrow().class.name),
+            Value::Module(module) => write!(f, "<module '{}'>", module.name),
+            Value::Image(image) => {
+                let image = image.borrow();
+                write!(f, "<image {} {}x{}>", mode_name(&image), image.width(), image.height())
+            }
+            Value::BoundMethod { receiver, method } => {
+                let owner = match receiver.as_ref() {
+                    Value::Instance(instance) => instance.borrow().class.name.clone(),
+                    other => other.type_name().to_string(),
+                };
+                match method.as_ref() {
+                    Value::Function { name, .. } => write!(f, "<bound method {} of {} object>", name, owner),
+                    Value::NativeFunction { name, .. } => write!(f, "<built-in method {} of {} object>", name, owner),
+                    other => write!(f, "<bound method {}>", other),
+                }
             }
-            Value::Function { name, .. } => format!("<function {}>", name),
         }
     }
+    
+    /// Like `write`, with strings quoted as they are inside a container.
+    fn write_repr(&self, f: &mut fmt::Formatter<'_>, open: &mut Vec<*const ()>) -> fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "'{}'", s),
+            other => other.write(f, open),
+        }
+    }
+}
+
+/// Orders two numbers exactly, so a large int is not rounded to compare it
+/// with a float. `None` if either is NaN or not a number.
+pub fn compare_numbers(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+        (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+        (Value::Int(a), Value::Float(b)) => compare_int_float(a, *b),
+        (Value::Float(a), Value::Int(b)) => compare_int_float(b, *a).map(Ordering::reverse),
+        _ => None,
+    }
+}
+
+fn compare_int_float(a: &BigInt, b: f64) -> Option<Ordering> {
+    if b.is_nan() {
+        return None;
+    }
+    if b.is_infinite() {
+        return Some(if b > 0.0 { Ordering::Less } else { Ordering::Greater });
+    }
+    
+    // Compare against the integer part, then let the fraction break a tie
+    let whole = BigInt::from_f64(b.trunc())?;
+    Some(a.cmp(&whole).then(if b.fract() > 0.0 {
+        Ordering::Less
+    } else if b.fract() < 0.0 {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }))
+}
+
+/// Shows a float the way Python does: integral values keep a `.0`, and very
+/// large or small magnitudes use exponent notation.
+pub fn float_repr(n: f64) -> String {
+    if n.is_nan() {
+        return "nan".to_string();
+    }
+    if n.is_infinite() {
+        return if n > 0.0 { "inf" } else { "-inf" }.to_string();
+    }
+    
+    if n != 0.0 && (n.abs() >= 1e16 || n.abs() < 1e-4) {
+        let text = format!("{:e}", n);
+        let (mantissa, exponent) = text.split_once('e').unwrap_or((&text, "0"));
+        let (sign, digits) = match exponent.strip_prefix('-') {
+            Some(digits) => ('-', digits),
+            None => ('+', exponent),
+        };
+        format!("{}e{}{:0>2}", mantissa, sign, digits)
+    } else if n.fract() == 0.0 {
+        format!("{:.1}", n)
+    } else {
+        n.to_string()
+    }
 }
 
-#[derive(Clone)]
+/// Shows a complex number the way Python does, as `(3+4j)`, or `4j` when
+/// the real part is a positive zero.
+pub fn complex_repr(n: Complex64) -> String {
+    // Parts drop the `.0` that a float would keep
+    let part = |x: f64| {
+        let text = float_repr(x);
+        text.strip_suffix(".0").map(str::to_string).unwrap_or(text)
+    };
+    
+    if n.re == 0.0 && n.re.is_sign_positive() {
+        format!("{}j", part(n.im))
+    } else {
+        let sign = if n.im.is_sign_negative() && !n.im.is_nan() { "" } else { "+" };
+        format!("({}{}{}j)", part(n.re), sign, part(n.im))
+    }
+}
+
+/// A scope shared between the code running in it and every closure defined there.
+pub type EnvRef = Rc<RefCell<Environment>>;
+
 pub struct Environment {
     variables: HashMap<String, Value>,
-    parent: Option<Box<Environment>>,
+    parent: Option<EnvRef>,
+}
+
+impl fmt::Debug for Environment {
+    // Closures make scopes cyclic, so only the names are shown
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Environment")
+            .field("variables", &self.variables.keys().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Environment {
@@ -76,22 +571,31 @@ impl Environment {
         }
     }
     
-    pub fn with_parent(parent: Environment) -> Self {
+    pub fn with_parent(parent: EnvRef) -> Self {
         Self {
             variables: HashMap::new(),
-            parent: Some(Box::new(parent)),
+            parent: Some(parent),
         }
     }
     
+    pub fn into_ref(self) -> EnvRef {
+        Rc::new(RefCell::new(self))
+    }
+    
     pub fn define(&mut self, name: String, value: Value) {
         self.variables.insert(name, value);
     }
     
+    /// Looks a name up in this scope only, ignoring enclosing scopes.
+    pub fn get_local(&self, name: &str) -> Option<Value> {
+        self.variables.get(name).cloned()
+    }
+    
     pub fn get(&self, name: &str) -> Result<Value> {
         if let Some(value) = self.variables.get(name) {
             Ok(value.clone())
         } else if let Some(ref parent) = self.parent {
-            parent.get(name)
+            parent.borrow().get(name)
         } else {
             Err(KyaroError::runtime_error(
                 format!("Undefined variable '{}'", name),
@@ -105,8 +609,8 @@ impl Environment {
         if self.variables.contains_key(name) {
             self.variables.insert(name.to_string(), value);
             Ok(())
-        } else if let Some(ref mut parent) = self.parent {
-            parent.set(name, value)
+        } else if let Some(ref parent) = self.parent {
+            parent.borrow_mut().set(name, value)
         } else {
             Err(KyaroError::runtime_error(
                 format!("Undefined variable '{}'", name),
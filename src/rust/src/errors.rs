@@ -1,3 +1,5 @@
+use crate::environment::Value;
+use std::borrow::Cow;
 use thiserror::Error;
 
 #[derive(Error, Debug, Clone)]
@@ -21,6 +23,20 @@ pub enum KyaroError {
         message: String,
         line: usize,
         column: usize,
+        file: Option<String>,
+        traceback: Vec<Frame>,
+    },
+    
+    /// An error raised by a Kyaro `throw` statement. `value` is the thrown
+    /// `Error` instance, which a catch block receives unchanged.
+    #[error("Error at line {line}, column {column}: {message}")]
+    UserError {
+        message: String,
+        line: usize,
+        column: usize,
+        file: Option<String>,
+        traceback: Vec<Frame>,
+        value: Box<Value>,
     },
     
     #[error("Error: {message}")]
@@ -49,6 +65,19 @@ impl KyaroError {
             message: message.into(),
             line,
             column,
+            file: None,
+            traceback: Vec::new(),
+        }
+    }
+    
+    pub fn user_error(value: Value, message: impl Into<String>, line: usize, column: usize) -> Self {
+        Self::UserError {
+            message: message.into(),
+            line,
+            column,
+            file: None,
+            traceback: Vec::new(),
+            value: Box::new(value),
         }
     }
     
@@ -57,6 +86,150 @@ impl KyaroError {
             message: message.into(),
         }
     }
+    
+    /// The category reported to Kyaro code that catches the error.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::LexerError { .. } => "lexer",
+            Self::ParserError { .. } => "parser",
+            Self::RuntimeError { .. } | Self::GenericError { .. } => "runtime",
+            Self::UserError { .. } => "user",
+        }
+    }
+    
+    pub fn message(&self) -> &str {
+        match self {
+            Self::LexerError { message, .. }
+            | Self::ParserError { message, .. }
+            | Self::RuntimeError { message, .. }
+            | Self::UserError { message, .. }
+            | Self::GenericError { message } => message,
+        }
+    }
+    
+    /// Line and column of the error, `(0, 0)` when unknown.
+    pub fn position(&self) -> (usize, usize) {
+        match self {
+            Self::LexerError { line, column, .. }
+            | Self::ParserError { line, column, .. }
+            | Self::RuntimeError { line, column, .. }
+            | Self::UserError { line, column, .. } => (*line, *column),
+            Self::GenericError { .. } => (0, 0),
+        }
+    }
+    
+    /// Fills in the position of a runtime error raised without one
+    /// (line 0). Errors that already know their position are unchanged.
+    pub fn with_position(mut self, line: usize, column: usize) -> Self {
+        if let Self::RuntimeError { line: error_line, column: error_column, .. }
+        | Self::UserError { line: error_line, column: error_column, .. } = &mut self
+        {
+            if *error_line == 0 {
+                *error_line = line;
+                *error_column = column;
+            }
+        }
+        self
+    }
+    
+    /// Records the call stack active where a runtime error was raised, and
+    /// the file that was running (`None` for the main program). Only the
+    /// innermost stack is kept as the error unwinds.
+    pub fn with_traceback(mut self, frames: &[Frame], file: Option<&str>) -> Self {
+        if let Self::RuntimeError { file: error_file, traceback, .. }
+        | Self::UserError { file: error_file, traceback, .. } = &mut self
+        {
+            if traceback.is_empty() {
+                *error_file = file.map(str::to_string);
+                *traceback = frames.to_vec();
+            }
+        }
+        self
+    }
+    
+    /// Renders the error the way it is reported to the user: runtime errors
+    /// get a Python-style traceback quoting the offending source lines.
+    /// `source` is the text of the main program, `filename` its name.
+    pub fn report(&self, source: &str, filename: &str) -> String {
+        let (line, file, traceback) = match self {
+            Self::RuntimeError { line, file, traceback, .. } | Self::UserError { line, file, traceback, .. }
+                if *line > 0 =>
+            {
+                (*line, file, traceback)
+            }
+            _ => return self.to_string(),
+        };
+        
+        let main = (filename, source);
+        let mut entries = Vec::with_capacity(traceback.len() + 1);
+        let mut function = "<module>";
+        
+        for frame in traceback {
+            entries.push(format_frame(frame.file.as_deref(), frame.line, function, main));
+            function = &frame.function;
+        }
+        entries.push(format_frame(file.as_deref(), line, function, main));
+        
+        // Runaway recursion repeats one entry; like Python, show it three
+        // times and count the rest
+        let mut report = String::from("Traceback (most recent call last):\n");
+        let mut index = 0;
+        while index < entries.len() {
+            let repeats = entries[index..].iter().take_while(|entry| **entry == entries[index]).count();
+            for _ in 0..repeats.min(3) {
+                report.push_str(&entries[index]);
+            }
+            if repeats > 3 {
+                let hidden = repeats - 3;
+                report.push_str(&format!("  [Previous line repeated {} more time{}]\n", hidden, if hidden == 1 { "" } else { "s" }));
+            }
+            index += repeats;
+        }
+        report.push_str(&self.to_string());
+        
+        report
+    }
+}
+
+/// A call in progress: the callee and the call site, in `file` or in the
+/// main program when `file` is `None`. Imports push a `<module>` frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub function: String,
+    pub file: Option<String>,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Frame {
+    pub fn new(function: impl Into<String>, file: Option<&str>, line: usize, column: usize) -> Self {
+        Self {
+            function: function.into(),
+            file: file.map(str::to_string),
+            line,
+            column,
+        }
+    }
+}
+
+fn format_frame(file: Option<&str>, line: usize, function: &str, main: (&str, &str)) -> String {
+    let (filename, source) = main;
+    let mut entry = format!("  File \"{}\", line {}, in {}\n", file.unwrap_or(filename), line, function);
+    
+    // Imported files are read back from disk to quote their lines. Names in
+    // angle brackets, such as earlier REPL inputs, have no file to read.
+    let text = match file {
+        None => Some(Cow::Borrowed(source)),
+        Some(path) if path == filename => Some(Cow::Borrowed(source)),
+        Some(path) if path.starts_with('<') => None,
+        Some(path) => std::fs::read_to_string(path).ok().map(Cow::Owned),
+    };
+    // Frames without a position (line 0) have nothing to quote
+    let excerpt = line.checked_sub(1).zip(text).and_then(|(index, text)| text.lines().nth(index).map(str::to_string));
+    if let Some(excerpt) = excerpt {
+        entry.push_str(&format!("    {}\n", excerpt.trim()));
+    }
+    entry
 }
 
 pub type Result<T> = std::result::Result<T, KyaroError>;
@@ -0,0 +1,280 @@
+use crate::environment::Value;
+use crate::errors::{KyaroError, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub type FileSystemFunction = fn(&[Value]) -> Result<Value>;
+
+pub fn get_file_system_functions() -> HashMap<String, FileSystemFunction> {
+    let mut functions = HashMap::new();
+    
+    // Reading and writing
+    functions.insert("fs_read_file".to_string(), fs_read_file as FileSystemFunction);
+    functions.insert("fs_read_lines".to_string(), fs_read_lines as FileSystemFunction);
+    functions.insert("fs_write_file".to_string(), fs_write_file as FileSystemFunction);
+    functions.insert("fs_append_file".to_string(), fs_append_file as FileSystemFunction);
+    
+    // Queries
+    functions.insert("fs_exists".to_string(), fs_exists as FileSystemFunction);
+    functions.insert("fs_is_file".to_string(), fs_is_file as FileSystemFunction);
+    functions.insert("fs_is_dir".to_string(), fs_is_dir as FileSystemFunction);
+    functions.insert("fs_get_size".to_string(), fs_get_size as FileSystemFunction);
+    functions.insert("fs_list_dir".to_string(), fs_list_dir as FileSystemFunction);
+    functions.insert("fs_get_cwd".to_string(), fs_get_cwd as FileSystemFunction);
+    
+    // Modifying the file system
+    functions.insert("fs_create_dir".to_string(), fs_create_dir as FileSystemFunction);
+    functions.insert("fs_delete_file".to_string(), fs_delete_file as FileSystemFunction);
+    functions.insert("fs_delete_dir".to_string(), fs_delete_dir as FileSystemFunction);
+    functions.insert("fs_copy_file".to_string(), fs_copy_file as FileSystemFunction);
+    functions.insert("fs_rename".to_string(), fs_rename as FileSystemFunction);
+    
+    // Path manipulation
+    functions.insert("fs_get_extension".to_string(), fs_get_extension as FileSystemFunction);
+    functions.insert("fs_get_stem".to_string(), fs_get_stem as FileSystemFunction);
+    functions.insert("path_join".to_string(), path_join as FileSystemFunction);
+    functions.insert("path_dirname".to_string(), path_dirname as FileSystemFunction);
+    functions.insert("path_basename".to_string(), path_basename as FileSystemFunction);
+    functions.insert("path_is_absolute".to_string(), path_is_absolute as FileSystemFunction);
+    
+    functions
+}
+
+/// Parameter names of the file system functions that accept keyword arguments.
+/// Optional parameters end in `?`.
+pub fn file_system_parameters(name: &str) -> Option<&'static [&'static str]> {
+    let parameters: &'static [&'static str] = match name {
+        "fs_write_file" | "fs_append_file" => &["path", "content"],
+        "fs_create_dir" => &["path", "parents?"],
+        "fs_delete_dir" => &["path", "recursive?"],
+        "fs_copy_file" | "fs_rename" => &["src", "dst"],
+        _ => return None,
+    };
+    Some(parameters)
+}
+
+fn string_arg<'a>(args: &'a [Value], index: usize, name: &str) -> Result<&'a str> {
+    match args.get(index) {
+        Some(Value::String(s)) => Ok(s),
+        _ => Err(KyaroError::runtime_error(format!("{}() requires a string path", name), 0, 0)),
+    }
+}
+
+fn expect_args(args: &[Value], count: usize, name: &str) -> Result<()> {
+    if args.len() != count {
+        let plural = if count == 1 { "argument" } else { "arguments" };
+        return Err(KyaroError::runtime_error(format!("{}() takes exactly {} {}", name, count, plural), 0, 0));
+    }
+    Ok(())
+}
+
+fn io_error(action: &str, error: std::io::Error) -> KyaroError {
+    KyaroError::runtime_error(format!("Cannot {}: {}", action, error), 0, 0)
+}
+
+// Reading and writing
+fn fs_read_file(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, "fs_read_file")?;
+    let path = string_arg(args, 0, "fs_read_file")?;
+    
+    fs::read_to_string(path)
+        .map(Value::String)
+        .map_err(|e| io_error("read file", e))
+}
+
+fn fs_read_lines(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, "fs_read_lines")?;
+    let path = string_arg(args, 0, "fs_read_lines")?;
+    
+    // Lines keep their terminators, as with Python's readlines()
+    let text = fs::read_to_string(path).map_err(|e| io_error("read file", e))?;
+    Ok(Value::list(text.split_inclusive('\n').map(|line| Value::String(line.to_string())).collect()))
+}
+
+fn fs_write_file(args: &[Value]) -> Result<Value> {
+    expect_args(args, 2, "fs_write_file")?;
+    let path = string_arg(args, 0, "fs_write_file")?;
+    
+    fs::write(path, args[1].to_string()).map_err(|e| io_error("write file", e))?;
+    Ok(Value::Null)
+}
+
+fn fs_append_file(args: &[Value]) -> Result<Value> {
+    use std::io::Write;
+    
+    expect_args(args, 2, "fs_append_file")?;
+    let path = string_arg(args, 0, "fs_append_file")?;
+    
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| io_error("append to file", e))?;
+    file.write_all(args[1].to_string().as_bytes()).map_err(|e| io_error("append to file", e))?;
+    Ok(Value::Null)
+}
+
+// Queries
+fn fs_exists(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, "fs_exists")?;
+    Ok(Value::Boolean(Path::new(string_arg(args, 0, "fs_exists")?).exists()))
+}
+
+fn fs_is_file(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, "fs_is_file")?;
+    Ok(Value::Boolean(Path::new(string_arg(args, 0, "fs_is_file")?).is_file()))
+}
+
+fn fs_is_dir(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, "fs_is_dir")?;
+    Ok(Value::Boolean(Path::new(string_arg(args, 0, "fs_is_dir")?).is_dir()))
+}
+
+fn fs_get_size(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, "fs_get_size")?;
+    let path = Path::new(string_arg(args, 0, "fs_get_size")?);
+    
+    // Directories report the total size of the files beneath them
+    fn total_size(path: &Path) -> std::io::Result<u64> {
+        let metadata = fs::metadata(path)?;
+        if !metadata.is_dir() {
+            return Ok(metadata.len());
+        }
+        
+        let mut total = 0;
+        for entry in fs::read_dir(path)? {
+            total += total_size(&entry?.path())?;
+        }
+        Ok(total)
+    }
+    
+    total_size(path)
+        .map(Value::int)
+        .map_err(|e| io_error("get size", e))
+}
+
+fn fs_list_dir(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, "fs_list_dir")?;
+    let path = string_arg(args, 0, "fs_list_dir")?;
+    
+    let mut names = Vec::new();
+    for entry in fs::read_dir(path).map_err(|e| io_error("list directory", e))? {
+        let entry = entry.map_err(|e| io_error("list directory", e))?;
+        names.push(Value::String(entry.file_name().to_string_lossy().into_owned()));
+    }
+    Ok(Value::list(names))
+}
+
+fn fs_get_cwd(args: &[Value]) -> Result<Value> {
+    expect_args(args, 0, "fs_get_cwd")?;
+    
+    std::env::current_dir()
+        .map(|dir| Value::String(dir.display().to_string()))
+        .map_err(|e| io_error("get current directory", e))
+}
+
+// Modifying the file system
+fn fs_create_dir(args: &[Value]) -> Result<Value> {
+    if args.is_empty() || args.len() > 2 {
+        return Err(KyaroError::runtime_error("fs_create_dir() takes 1-2 arguments".to_string(), 0, 0));
+    }
+    let path = string_arg(args, 0, "fs_create_dir")?;
+    let parents = args.get(1).is_some_and(Value::is_truthy);
+    
+    let result = if parents { fs::create_dir_all(path) } else { fs::create_dir(path) };
+    result.map_err(|e| io_error("create directory", e))?;
+    Ok(Value::Null)
+}
+
+fn fs_delete_file(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, "fs_delete_file")?;
+    
+    fs::remove_file(string_arg(args, 0, "fs_delete_file")?).map_err(|e| io_error("delete file", e))?;
+    Ok(Value::Null)
+}
+
+fn fs_delete_dir(args: &[Value]) -> Result<Value> {
+    if args.is_empty() || args.len() > 2 {
+        return Err(KyaroError::runtime_error("fs_delete_dir() takes 1-2 arguments".to_string(), 0, 0));
+    }
+    let path = string_arg(args, 0, "fs_delete_dir")?;
+    let recursive = args.get(1).is_some_and(Value::is_truthy);
+    
+    let result = if recursive { fs::remove_dir_all(path) } else { fs::remove_dir(path) };
+    result.map_err(|e| io_error("delete directory", e))?;
+    Ok(Value::Null)
+}
+
+fn fs_copy_file(args: &[Value]) -> Result<Value> {
+    expect_args(args, 2, "fs_copy_file")?;
+    let src = string_arg(args, 0, "fs_copy_file")?;
+    let dst = string_arg(args, 1, "fs_copy_file")?;
+    
+    // Copying into a directory keeps the file name, like shutil.copy2
+    let mut target = PathBuf::from(dst);
+    if target.is_dir() {
+        if let Some(name) = Path::new(src).file_name() {
+            target.push(name);
+        }
+    }
+    
+    fs::copy(src, target).map_err(|e| io_error("copy file", e))?;
+    Ok(Value::Null)
+}
+
+fn fs_rename(args: &[Value]) -> Result<Value> {
+    expect_args(args, 2, "fs_rename")?;
+    let src = string_arg(args, 0, "fs_rename")?;
+    let dst = string_arg(args, 1, "fs_rename")?;
+    
+    fs::rename(src, dst).map_err(|e| io_error("rename", e))?;
+    Ok(Value::Null)
+}
+
+// Path manipulation
+fn fs_get_extension(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, "fs_get_extension")?;
+    let path = Path::new(string_arg(args, 0, "fs_get_extension")?);
+    
+    let extension = path.extension().map_or_else(String::new, |ext| format!(".{}", ext.to_string_lossy()));
+    Ok(Value::String(extension))
+}
+
+fn fs_get_stem(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, "fs_get_stem")?;
+    let path = Path::new(string_arg(args, 0, "fs_get_stem")?);
+    
+    Ok(Value::String(path.file_stem().map_or_else(String::new, |stem| stem.to_string_lossy().into_owned())))
+}
+
+fn path_join(args: &[Value]) -> Result<Value> {
+    if args.is_empty() {
+        return Err(KyaroError::runtime_error("path_join() requires at least one argument".to_string(), 0, 0));
+    }
+    
+    let mut path = PathBuf::new();
+    for index in 0..args.len() {
+        path.push(string_arg(args, index, "path_join")?);
+    }
+    Ok(Value::String(path.display().to_string()))
+}
+
+fn path_dirname(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, "path_dirname")?;
+    let path = Path::new(string_arg(args, 0, "path_dirname")?);
+    
+    Ok(Value::String(path.parent().map_or_else(String::new, |dir| dir.display().to_string())))
+}
+
+fn path_basename(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, "path_basename")?;
+    let path = Path::new(string_arg(args, 0, "path_basename")?);
+    
+    Ok(Value::String(path.file_name().map_or_else(String::new, |name| name.to_string_lossy().into_owned())))
+}
+
+fn path_is_absolute(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, "path_is_absolute")?;
+    Ok(Value::Boolean(Path::new(string_arg(args, 0, "path_is_absolute")?).is_absolute()))
+}
@@ -0,0 +1,635 @@
+use crate::environment::{ImageRef, Value};
+use crate::errors::{KyaroError, Result};
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GenericImageView, GrayAlphaImage, GrayImage, ImageFormat, Luma, LumaA};
+use num_traits::ToPrimitive;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+pub type ImageFunction = fn(&[Value]) -> Result<Value>;
+
+pub fn get_image_functions() -> HashMap<String, ImageFunction> {
+    let mut functions = HashMap::new();
+    
+    // Loading, saving and creating
+    functions.insert("image_load".to_string(), image_load as ImageFunction);
+    functions.insert("image_save".to_string(), image_save as ImageFunction);
+    functions.insert("image_new".to_string(), image_new as ImageFunction);
+    functions.insert("image_copy".to_string(), image_copy as ImageFunction);
+    functions.insert("image_convert".to_string(), image_convert as ImageFunction);
+    
+    // Queries
+    functions.insert("image_size".to_string(), image_size as ImageFunction);
+    functions.insert("image_mode".to_string(), image_mode as ImageFunction);
+    
+    // Geometry
+    functions.insert("image_resize".to_string(), image_resize as ImageFunction);
+    functions.insert("image_thumbnail".to_string(), image_thumbnail as ImageFunction);
+    functions.insert("image_crop".to_string(), image_crop as ImageFunction);
+    functions.insert("image_rotate".to_string(), image_rotate as ImageFunction);
+    functions.insert("image_flip_horizontal".to_string(), image_flip_horizontal as ImageFunction);
+    functions.insert("image_flip_vertical".to_string(), image_flip_vertical as ImageFunction);
+    functions.insert("image_transpose".to_string(), image_transpose as ImageFunction);
+    
+    // Filters and enhancements
+    functions.insert("image_blur".to_string(), image_blur as ImageFunction);
+    functions.insert("image_grayscale".to_string(), image_grayscale as ImageFunction);
+    functions.insert("image_invert".to_string(), image_invert as ImageFunction);
+    functions.insert("image_brightness".to_string(), image_brightness as ImageFunction);
+    functions.insert("image_contrast".to_string(), image_contrast as ImageFunction);
+    
+    // Pixels and composition
+    functions.insert("image_get_pixel".to_string(), image_get_pixel as ImageFunction);
+    functions.insert("image_put_pixel".to_string(), image_put_pixel as ImageFunction);
+    functions.insert("image_paste".to_string(), image_paste as ImageFunction);
+    functions.insert("image_blend".to_string(), image_blend as ImageFunction);
+    
+    functions
+}
+
+/// Parameter names of the image functions that accept keyword arguments.
+/// Optional parameters end in `?`.
+pub fn image_parameters(name: &str) -> Option<&'static [&'static str]> {
+    let parameters: &'static [&'static str] = match name {
+        "image_save" => &["img", "path", "format?"],
+        "image_new" => &["mode", "size", "color?"],
+        "image_rotate" => &["img", "angle", "expand?", "fillcolor?"],
+        "image_blur" => &["img", "radius?"],
+        "image_paste" => &["img", "im", "box?"],
+        "image_blend" => &["img1", "img2", "alpha"],
+        _ => return None,
+    };
+    Some(parameters)
+}
+
+/// The PIL mode name of an image. Images are always stored in one of the
+/// four 8-bit modes, converting anything else when it is loaded.
+pub fn mode_name(image: &DynamicImage) -> &'static str {
+    match image {
+        DynamicImage::ImageLuma8(_) => "L",
+        DynamicImage::ImageLumaA8(_) => "LA",
+        DynamicImage::ImageRgb8(_) => "RGB",
+        _ => "RGBA",
+    }
+}
+
+fn normalize(image: DynamicImage) -> DynamicImage {
+    match image {
+        DynamicImage::ImageLuma8(_)
+        | DynamicImage::ImageLumaA8(_)
+        | DynamicImage::ImageRgb8(_)
+        | DynamicImage::ImageRgba8(_) => image,
+        other => match (other.color().has_color(), other.color().has_alpha()) {
+            (false, false) => DynamicImage::ImageLuma8(other.to_luma8()),
+            (false, true) => DynamicImage::ImageLumaA8(other.to_luma_alpha8()),
+            (true, false) => DynamicImage::ImageRgb8(other.to_rgb8()),
+            (true, true) => DynamicImage::ImageRgba8(other.to_rgba8()),
+        },
+    }
+}
+
+fn wrap(image: DynamicImage) -> Value {
+    Value::Image(Rc::new(RefCell::new(image)))
+}
+
+fn expect_args(args: &[Value], min: usize, max: usize, name: &str) -> Result<()> {
+    if args.len() < min || args.len() > max {
+        let message = if min == max {
+            let plural = if min == 1 { "argument" } else { "arguments" };
+            format!("{}() takes exactly {} {}", name, min, plural)
+        } else {
+            format!("{}() takes {}-{} arguments", name, min, max)
+        };
+        return Err(KyaroError::runtime_error(message, 0, 0));
+    }
+    Ok(())
+}
+
+/// An optional argument, with null standing in for one that was not given.
+fn optional_arg(args: &[Value], index: usize) -> Option<&Value> {
+    args.get(index).filter(|value| !matches!(value, Value::Null))
+}
+
+fn image_arg(args: &[Value], index: usize, name: &str) -> Result<ImageRef> {
+    match args.get(index) {
+        Some(Value::Image(image)) => Ok(Rc::clone(image)),
+        _ => Err(KyaroError::runtime_error(format!("{}() requires an image", name), 0, 0)),
+    }
+}
+
+fn string_arg<'a>(args: &'a [Value], index: usize, name: &str, what: &str) -> Result<&'a str> {
+    match args.get(index) {
+        Some(Value::String(s)) => Ok(s),
+        _ => Err(KyaroError::runtime_error(format!("{}() requires a string {}", name, what), 0, 0)),
+    }
+}
+
+fn number_arg(args: &[Value], index: usize, name: &str, what: &str) -> Result<f64> {
+    args.get(index)
+        .and_then(Value::as_f64)
+        .ok_or_else(|| KyaroError::runtime_error(format!("{}() requires a number for {}", name, what), 0, 0))
+}
+
+/// A list of `count` integers, such as a point, a size or a box.
+fn ints_arg(args: &[Value], index: usize, count: usize, name: &str, what: &str) -> Result<Vec<i64>> {
+    let error = || KyaroError::runtime_error(format!("{}() requires {} as a list of {} integers", name, what, count), 0, 0);
+    
+    let Some(Value::List(items)) = args.get(index) else {
+        return Err(error());
+    };
+    let items = items.borrow();
+    if items.len() != count {
+        return Err(error());
+    }
+    items
+        .iter()
+        .map(|item| match item {
+            Value::Int(n) => n.to_i64().ok_or_else(error),
+            _ => Err(error()),
+        })
+        .collect()
+}
+
+fn size_arg(args: &[Value], index: usize, name: &str) -> Result<(u32, u32)> {
+    let size = ints_arg(args, index, 2, name, "a size")?;
+    match (u32::try_from(size[0]), u32::try_from(size[1])) {
+        (Ok(width), Ok(height)) if width > 0 && height > 0 => Ok((width, height)),
+        _ => Err(KyaroError::runtime_error(
+            format!("{}() requires a positive width and height, got {}x{}", name, size[0], size[1]),
+            0,
+            0,
+        )),
+    }
+}
+
+/// The pixel at a point argument, as an offset into the image's bytes.
+fn pixel_offset(image: &DynamicImage, args: &[Value], index: usize, name: &str) -> Result<usize> {
+    let point = ints_arg(args, index, 2, name, "a point")?;
+    let (width, height) = image.dimensions();
+    if !(0..i64::from(width)).contains(&point[0]) || !(0..i64::from(height)).contains(&point[1]) {
+        return Err(KyaroError::runtime_error(
+            format!("{}() point [{}, {}] is outside the {}x{} image", name, point[0], point[1], width, height),
+            0,
+            0,
+        ));
+    }
+    let channels = usize::from(image.color().channel_count());
+    Ok((point[1] as usize * width as usize + point[0] as usize) * channels)
+}
+
+/// Parses a color for an image with `channels` channels. An integer sets
+/// every channel, and a list gives one value per channel; the alpha
+/// channel may be left out of a list, making the color opaque.
+fn color_arg(value: Option<&Value>, channels: usize, name: &str) -> Result<Vec<u8>> {
+    let error = || {
+        KyaroError::runtime_error(
+            format!("{}() requires a color as an integer or a list of {} integers from 0 to 255", name, channels),
+            0,
+            0,
+        )
+    };
+    let channel = |value: &Value| match value {
+        Value::Int(n) => n.to_u8().ok_or_else(error),
+        _ => Err(error()),
+    };
+    
+    match value {
+        None => Ok(vec![0; channels]),
+        Some(Value::List(items)) => {
+            let mut color = items.borrow().iter().map(channel).collect::<Result<Vec<u8>>>()?;
+            let has_alpha = matches!(channels, 2 | 4);
+            if has_alpha && color.len() + 1 == channels {
+                color.push(u8::MAX);
+            }
+            if color.len() != channels {
+                return Err(error());
+            }
+            Ok(color)
+        }
+        Some(value) => Ok(vec![channel(value)?; channels]),
+    }
+}
+
+fn bytes_mut(image: &mut DynamicImage) -> &mut [u8] {
+    match image {
+        DynamicImage::ImageLuma8(buffer) => buffer,
+        DynamicImage::ImageLumaA8(buffer) => buffer,
+        DynamicImage::ImageRgb8(buffer) => buffer,
+        DynamicImage::ImageRgba8(buffer) => buffer,
+        _ => unreachable!("images are normalized to 8-bit modes"),
+    }
+}
+
+/// Applies `f` to every color channel of every pixel, leaving alpha alone.
+fn map_colors(image: &mut DynamicImage, f: impl Fn(u8) -> u8) {
+    let channels = usize::from(image.color().channel_count());
+    let color_channels = if matches!(channels, 2 | 4) { channels - 1 } else { channels };
+    for pixel in bytes_mut(image).chunks_exact_mut(channels) {
+        for value in &mut pixel[..color_channels] {
+            *value = f(*value);
+        }
+    }
+}
+
+/// ITU-R 601-2 luma, computed in fixed point the way PIL does.
+fn luma(rgb: &[u8]) -> u8 {
+    let weighted = u32::from(rgb[0]) * 19595 + u32::from(rgb[1]) * 38470 + u32::from(rgb[2]) * 7471;
+    ((weighted + 0x8000) >> 16) as u8
+}
+
+/// Interpolates from `from` towards `to`, extrapolating past them for
+/// factors outside 0 to 1, as PIL's enhancers and blend do.
+fn mix(from: u8, to: u8, factor: f64) -> u8 {
+    let value = f64::from(from) + factor * (f64::from(to) - f64::from(from));
+    value.clamp(0.0, 255.0) as u8
+}
+
+/// An image of one of the supported modes with every channel zero.
+fn blank(mode: &str, width: u32, height: u32) -> DynamicImage {
+    match mode {
+        "L" => DynamicImage::new_luma8(width, height),
+        "LA" => DynamicImage::new_luma_a8(width, height),
+        "RGB" => DynamicImage::new_rgb8(width, height),
+        _ => DynamicImage::new_rgba8(width, height),
+    }
+}
+
+fn convert(image: &DynamicImage, mode: &str, name: &str) -> Result<DynamicImage> {
+    let rgba = image.to_rgba8();
+    let converted = match mode {
+        "L" => DynamicImage::ImageLuma8(GrayImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+            Luma([luma(&rgba.get_pixel(x, y).0)])
+        })),
+        "LA" => DynamicImage::ImageLumaA8(GrayAlphaImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+            let pixel = rgba.get_pixel(x, y).0;
+            LumaA([luma(&pixel), pixel[3]])
+        })),
+        "RGB" => DynamicImage::ImageRgb8(image.to_rgb8()),
+        "RGBA" => DynamicImage::ImageRgba8(rgba),
+        _ => return Err(unsupported_mode(mode, name)),
+    };
+    Ok(converted)
+}
+
+fn unsupported_mode(mode: &str, name: &str) -> KyaroError {
+    KyaroError::runtime_error(
+        format!("{}() does not support image mode '{}' (use L, LA, RGB or RGBA)", name, mode),
+        0,
+        0,
+    )
+}
+
+fn image_error(action: &str, error: image::ImageError) -> KyaroError {
+    KyaroError::runtime_error(format!("Cannot {}: {}", action, error), 0, 0)
+}
+
+// Loading, saving and creating
+fn image_load(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, 1, "image_load")?;
+    let path = string_arg(args, 0, "image_load", "path")?;
+    
+    let image = image::open(path).map_err(|e| image_error("load image", e))?;
+    Ok(wrap(normalize(image)))
+}
+
+fn image_save(args: &[Value]) -> Result<Value> {
+    expect_args(args, 2, 3, "image_save")?;
+    let image = image_arg(args, 0, "image_save")?;
+    let path = string_arg(args, 1, "image_save", "path")?;
+    
+    let result = match optional_arg(args, 2) {
+        Some(_) => {
+            let format = string_arg(args, 2, "image_save", "format")?;
+            let format = ImageFormat::from_extension(format).ok_or_else(|| {
+                KyaroError::runtime_error(format!("image_save() does not know the format '{}'", format), 0, 0)
+            })?;
+            image.borrow().save_with_format(path, format)
+        }
+        None => image.borrow().save(path),
+    };
+    result.map_err(|e| image_error("save image", e))?;
+    Ok(Value::Null)
+}
+
+fn image_new(args: &[Value]) -> Result<Value> {
+    expect_args(args, 2, 3, "image_new")?;
+    let mode = string_arg(args, 0, "image_new", "mode")?;
+    let (width, height) = size_arg(args, 1, "image_new")?;
+    
+    if !matches!(mode, "L" | "LA" | "RGB" | "RGBA") {
+        return Err(unsupported_mode(mode, "image_new"));
+    }
+    let mut image = blank(mode, width, height);
+    let color = color_arg(optional_arg(args, 2), usize::from(image.color().channel_count()), "image_new")?;
+    for pixel in bytes_mut(&mut image).chunks_exact_mut(color.len()) {
+        pixel.copy_from_slice(&color);
+    }
+    Ok(wrap(image))
+}
+
+fn image_copy(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, 1, "image_copy")?;
+    let image = image_arg(args, 0, "image_copy")?;
+    
+    let copy = image.borrow().clone();
+    Ok(wrap(copy))
+}
+
+fn image_convert(args: &[Value]) -> Result<Value> {
+    expect_args(args, 2, 2, "image_convert")?;
+    let image = image_arg(args, 0, "image_convert")?;
+    let mode = string_arg(args, 1, "image_convert", "mode")?;
+    
+    let converted = convert(&image.borrow(), mode, "image_convert")?;
+    Ok(wrap(converted))
+}
+
+// Queries
+fn image_size(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, 1, "image_size")?;
+    let (width, height) = image_arg(args, 0, "image_size")?.borrow().dimensions();
+    Ok(Value::list(vec![Value::int(width), Value::int(height)]))
+}
+
+fn image_mode(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, 1, "image_mode")?;
+    let mode = mode_name(&image_arg(args, 0, "image_mode")?.borrow());
+    Ok(Value::String(mode.to_string()))
+}
+
+// Geometry
+fn image_resize(args: &[Value]) -> Result<Value> {
+    expect_args(args, 2, 2, "image_resize")?;
+    let image = image_arg(args, 0, "image_resize")?;
+    let (width, height) = size_arg(args, 1, "image_resize")?;
+    
+    let resized = image.borrow().resize_exact(width, height, FilterType::Lanczos3);
+    Ok(wrap(resized))
+}
+
+/// Shrinks the image in place to fit within the size, keeping its aspect
+/// ratio. Images that already fit are left alone.
+fn image_thumbnail(args: &[Value]) -> Result<Value> {
+    expect_args(args, 2, 2, "image_thumbnail")?;
+    let image = image_arg(args, 0, "image_thumbnail")?;
+    let (width, height) = size_arg(args, 1, "image_thumbnail")?;
+    
+    let mut image = image.borrow_mut();
+    if image.width() > width || image.height() > height {
+        *image = image.resize(width, height, FilterType::Lanczos3);
+    }
+    Ok(Value::Null)
+}
+
+/// Crops to a `[left, top, right, bottom]` box. Parts of the box outside
+/// the image are filled with zeros, as in PIL.
+fn image_crop(args: &[Value]) -> Result<Value> {
+    expect_args(args, 2, 2, "image_crop")?;
+    let image = image_arg(args, 0, "image_crop")?;
+    let bounds = ints_arg(args, 1, 4, "image_crop", "a box")?;
+    
+    let (left, top, right, bottom) = (bounds[0], bounds[1], bounds[2], bounds[3]);
+    let (Ok(width), Ok(height)) = (u32::try_from(right - left), u32::try_from(bottom - top)) else {
+        return Err(KyaroError::runtime_error(
+            format!("image_crop() box [{}, {}, {}, {}] has a negative size", left, top, right, bottom),
+            0,
+            0,
+        ));
+    };
+    
+    let image = image.borrow();
+    let mut cropped = blank(mode_name(&image), width, height);
+    imageops::replace(&mut cropped, &*image, -left, -top);
+    Ok(wrap(cropped))
+}
+
+/// Rotates counter-clockwise by `angle` degrees about the center, keeping
+/// the image's size unless `expand` is set, and filling the uncovered
+/// corners with `fillcolor`. Pixels are mapped back to their nearest
+/// source pixel with the same affine transform PIL uses.
+fn image_rotate(args: &[Value]) -> Result<Value> {
+    expect_args(args, 2, 4, "image_rotate")?;
+    let image = image_arg(args, 0, "image_rotate")?;
+    let angle = number_arg(args, 1, "image_rotate", "the angle")?;
+    let expand = optional_arg(args, 2).is_some_and(Value::is_truthy);
+    
+    let image = image.borrow();
+    let channels = usize::from(image.color().channel_count());
+    let fill = color_arg(optional_arg(args, 3), channels, "image_rotate")?;
+    
+    // Quarter turns that keep every pixel take PIL's exact fast paths
+    match angle.rem_euclid(360.0) {
+        0.0 => return Ok(wrap(image.clone())),
+        180.0 => return Ok(wrap(image.rotate180())),
+        90.0 if expand || image.width() == image.height() => return Ok(wrap(image.rotate270())),
+        270.0 if expand || image.width() == image.height() => return Ok(wrap(image.rotate90())),
+        _ => {}
+    }
+    
+    // Rounding like PIL keeps the other multiples of 90 degrees exact
+    let round = |value: f64| (value * 1e15).round() / 1e15;
+    let (sin, cos) = (-angle.to_radians()).sin_cos();
+    let (a, b, d, e) = (round(cos), round(sin), round(-sin), round(cos));
+    let transform = |x: f64, y: f64| (a * x + b * y, d * x + e * y);
+    
+    let (width, height) = (f64::from(image.width()), f64::from(image.height()));
+    let (x, y) = transform(-width / 2.0, -height / 2.0);
+    let (mut c, mut f) = (x + width / 2.0, y + height / 2.0);
+    
+    let (new_width, new_height) = if expand {
+        let corners = [(0.0, 0.0), (width, 0.0), (width, height), (0.0, height)].map(|(x, y)| {
+            let (x, y) = transform(x, y);
+            (x + c, y + f)
+        });
+        let extent = |values: [f64; 4]| {
+            values.iter().copied().fold(f64::NEG_INFINITY, f64::max).ceil()
+                - values.iter().copied().fold(f64::INFINITY, f64::min).floor()
+        };
+        let new_width = extent(corners.map(|(x, _)| x));
+        let new_height = extent(corners.map(|(_, y)| y));
+        
+        let (x, y) = transform(-(new_width - width) / 2.0, -(new_height - height) / 2.0);
+        (c, f) = (x + c, y + f);
+        (new_width as u32, new_height as u32)
+    } else {
+        (image.width(), image.height())
+    };
+    
+    let source = image.as_bytes();
+    let mut rotated = blank(mode_name(&image), new_width, new_height);
+    for (index, pixel) in bytes_mut(&mut rotated).chunks_exact_mut(channels).enumerate() {
+        let x = (index % new_width as usize) as f64 + 0.5;
+        let y = (index / new_width as usize) as f64 + 0.5;
+        let (source_x, source_y) = transform(x, y);
+        let (source_x, source_y) = (source_x + c, source_y + f);
+        
+        if (0.0..width).contains(&source_x) && (0.0..height).contains(&source_y) {
+            let offset = (source_y as usize * image.width() as usize + source_x as usize) * channels;
+            pixel.copy_from_slice(&source[offset..offset + channels]);
+        } else {
+            pixel.copy_from_slice(&fill);
+        }
+    }
+    Ok(wrap(rotated))
+}
+
+fn image_flip_horizontal(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, 1, "image_flip_horizontal")?;
+    let flipped = image_arg(args, 0, "image_flip_horizontal")?.borrow().fliph();
+    Ok(wrap(flipped))
+}
+
+fn image_flip_vertical(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, 1, "image_flip_vertical")?;
+    let flipped = image_arg(args, 0, "image_flip_vertical")?.borrow().flipv();
+    Ok(wrap(flipped))
+}
+
+/// Swaps the x and y axes, mirroring the image along its main diagonal.
+fn image_transpose(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, 1, "image_transpose")?;
+    let transposed = image_arg(args, 0, "image_transpose")?.borrow().rotate90().fliph();
+    Ok(wrap(transposed))
+}
+
+// Filters and enhancements
+fn image_blur(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, 2, "image_blur")?;
+    let image = image_arg(args, 0, "image_blur")?;
+    let radius = match optional_arg(args, 1) {
+        Some(_) => number_arg(args, 1, "image_blur", "the radius")?,
+        None => 2.0,
+    };
+    
+    let image = image.borrow();
+    let blurred = if radius > 0.0 { image.blur(radius as f32) } else { image.clone() };
+    Ok(wrap(blurred))
+}
+
+fn image_grayscale(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, 1, "image_grayscale")?;
+    let gray = convert(&image_arg(args, 0, "image_grayscale")?.borrow(), "L", "image_grayscale")?;
+    Ok(wrap(gray))
+}
+
+fn image_invert(args: &[Value]) -> Result<Value> {
+    expect_args(args, 1, 1, "image_invert")?;
+    let mut inverted = image_arg(args, 0, "image_invert")?.borrow().clone();
+    map_colors(&mut inverted, |value| u8::MAX - value);
+    Ok(wrap(inverted))
+}
+
+/// Scales every color towards black (factor below 1) or away from it.
+fn image_brightness(args: &[Value]) -> Result<Value> {
+    expect_args(args, 2, 2, "image_brightness")?;
+    let image = image_arg(args, 0, "image_brightness")?;
+    let factor = number_arg(args, 1, "image_brightness", "the factor")?;
+    
+    let mut brightened = image.borrow().clone();
+    map_colors(&mut brightened, |value| mix(0, value, factor));
+    Ok(wrap(brightened))
+}
+
+/// Moves every color towards the image's mean gray level (factor below 1)
+/// or away from it.
+fn image_contrast(args: &[Value]) -> Result<Value> {
+    expect_args(args, 2, 2, "image_contrast")?;
+    let image = image_arg(args, 0, "image_contrast")?;
+    let factor = number_arg(args, 1, "image_contrast", "the factor")?;
+    
+    let mut adjusted = image.borrow().clone();
+    let gray = convert(&adjusted, "L", "image_contrast")?;
+    let total: u64 = gray.as_bytes().iter().map(|&value| u64::from(value)).sum();
+    let mean = (total as f64 / gray.as_bytes().len() as f64 + 0.5) as u8;
+    
+    map_colors(&mut adjusted, |value| mix(mean, value, factor));
+    Ok(wrap(adjusted))
+}
+
+// Pixels and composition
+/// An integer for single-channel images, otherwise a list of channels.
+fn image_get_pixel(args: &[Value]) -> Result<Value> {
+    expect_args(args, 2, 2, "image_get_pixel")?;
+    let image = image_arg(args, 0, "image_get_pixel")?;
+    
+    let image = image.borrow();
+    let offset = pixel_offset(&image, args, 1, "image_get_pixel")?;
+    let channels = usize::from(image.color().channel_count());
+    let pixel = &image.as_bytes()[offset..offset + channels];
+    
+    match pixel {
+        [value] => Ok(Value::int(*value)),
+        _ => Ok(Value::list(pixel.iter().map(|&value| Value::int(value)).collect())),
+    }
+}
+
+fn image_put_pixel(args: &[Value]) -> Result<Value> {
+    expect_args(args, 3, 3, "image_put_pixel")?;
+    let image = image_arg(args, 0, "image_put_pixel")?;
+    
+    let mut image = image.borrow_mut();
+    let offset = pixel_offset(&image, args, 1, "image_put_pixel")?;
+    let color = color_arg(args.get(2), usize::from(image.color().channel_count()), "image_put_pixel")?;
+    
+    bytes_mut(&mut image)[offset..offset + color.len()].copy_from_slice(&color);
+    Ok(Value::Null)
+}
+
+/// Pastes `im` onto the image in place with its top left corner at `box`,
+/// which is `[x, y]` or a `[left, top, right, bottom]` box the size of `im`.
+/// `im` is converted to the image's mode first.
+fn image_paste(args: &[Value]) -> Result<Value> {
+    expect_args(args, 2, 3, "image_paste")?;
+    let image = image_arg(args, 0, "image_paste")?;
+    
+    // Convert before borrowing the target mutably, which also allows
+    // pasting an image onto itself
+    let pasted = convert(&image_arg(args, 1, "image_paste")?.borrow(), mode_name(&image.borrow()), "image_paste")?;
+    
+    let (x, y) = match optional_arg(args, 2) {
+        None => (0, 0),
+        Some(Value::List(items)) if items.borrow().len() == 4 => {
+            let bounds = ints_arg(args, 2, 4, "image_paste", "a box")?;
+            if (bounds[2] - bounds[0], bounds[3] - bounds[1]) != (i64::from(pasted.width()), i64::from(pasted.height())) {
+                return Err(KyaroError::runtime_error(
+                    "image_paste() box size does not match the pasted image".to_string(),
+                    0,
+                    0,
+                ));
+            }
+            (bounds[0], bounds[1])
+        }
+        Some(_) => {
+            let point = ints_arg(args, 2, 2, "image_paste", "a point")?;
+            (point[0], point[1])
+        }
+    };
+    
+    imageops::replace(&mut *image.borrow_mut(), &pasted, x, y);
+    Ok(Value::Null)
+}
+
+/// Interpolates between two images of the same mode and size:
+/// `img1 * (1 - alpha) + img2 * alpha`, on every channel.
+fn image_blend(args: &[Value]) -> Result<Value> {
+    expect_args(args, 3, 3, "image_blend")?;
+    let first = image_arg(args, 0, "image_blend")?;
+    let second = image_arg(args, 1, "image_blend")?;
+    let alpha = number_arg(args, 2, "image_blend", "alpha")?;
+    
+    let mut blended = first.borrow().clone();
+    let second = second.borrow();
+    if mode_name(&blended) != mode_name(&second) || blended.dimensions() != second.dimensions() {
+        return Err(KyaroError::runtime_error(
+            "image_blend() requires images of the same mode and size".to_string(),
+            0,
+            0,
+        ));
+    }
+    
+    for (value, &other) in bytes_mut(&mut blended).iter_mut().zip(second.as_bytes()) {
+        *value = mix(*value, other, alpha);
+    }
+    Ok(wrap(blended))
+}
@@ -1,10 +1,123 @@
-use crate::errors::{KyaroError, Result};
+use crate::errors::{Frame, KyaroError, Result};
 use crate::ast_nodes::*;
-use crate::environment::{Environment, Value};
+use crate::environment::{Class, DictKey, EnvRef, Environment, Instance, Module, Parameters, Value};
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use indexmap::IndexMap;
+use num_bigint::BigInt;
+use num_complex::Complex64;
+use num_integer::Integer;
+use num_traits::{Pow, Signed, ToPrimitive, Zero};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use crate::token_types::TokenType;
+use crate::builtins::{builtin_parameters, complex_divide, complex_pow, format_value, get_builtin_functions, get_context_functions, get_string_functions, range_iter, NativeContext, NativeFn};
+use crate::ai_ml_functions::{ai_ml_parameters, get_ai_ml_context_functions, get_ai_ml_functions};
+use crate::file_system_functions::{file_system_parameters, get_file_system_functions};
+use crate::image_functions::{get_image_functions, image_parameters};
+use crate::native_modules::get_native_module;
+
+/// Non-local exits unwind through `evaluate` on the error channel until the
+/// enclosing loop or function call handles them.
+enum ControlFlow {
+    Error(KyaroError),
+    Return(Box<Value>, Span),
+    Break(Span),
+    Continue(Span),
+}
+
+impl ControlFlow {
+    /// Gives an unpositioned runtime error the span of the node it escaped from.
+    fn at(self, span: Span) -> Self {
+        match self {
+            ControlFlow::Error(error) => ControlFlow::Error(error.with_position(span.line, span.column)),
+            other => other,
+        }
+    }
+    
+    /// Converts a signal that escaped its valid context into an error.
+    fn into_error(self) -> KyaroError {
+        match self {
+            ControlFlow::Error(error) => error,
+            ControlFlow::Return(_, span) => {
+                KyaroError::runtime_error("'return' outside function", span.line, span.column)
+            }
+            ControlFlow::Break(span) => KyaroError::runtime_error("'break' outside loop", span.line, span.column),
+            ControlFlow::Continue(span) => {
+                KyaroError::runtime_error("'continue' outside loop", span.line, span.column)
+            }
+        }
+    }
+}
+
+impl From<KyaroError> for ControlFlow {
+    fn from(error: KyaroError) -> Self {
+        ControlFlow::Error(error)
+    }
+}
+
+type EvalResult<T = Option<Value>> = std::result::Result<T, ControlFlow>;
+
+/// How many Kyaro function calls may be nested before a call raises
+/// "maximum recursion depth exceeded", as in Python.
+pub const MAX_CALL_DEPTH: usize = 1000;
+
+/// Arguments passed as `name: value`, in call order.
+type KeywordArguments = Vec<(String, Value)>;
+
+/// An assignment target with its object and index already evaluated, so a
+/// compound assignment reads and writes the same slot.
+enum Place {
+    Variable(String),
+    Item { container: Value, key: Value },
+    Member { object: Value, name: String },
+}
+
+/// The context a native function is called with. Functions it calls back
+/// get a frame at the native call's site, so tracebacks pass through it.
+struct NativeCall<'a> {
+    interpreter: &'a mut Interpreter,
+    call_site: Span,
+}
+
+impl NativeContext for NativeCall<'_> {
+    fn call(&mut self, function: &Value, arguments: Vec<Value>) -> Result<Value> {
+        self.interpreter
+            .call_value(function.clone(), arguments, Vec::new(), self.call_site)
+            .map(|value| value.unwrap_or(Value::Null))
+            .map_err(|flow| flow.at(self.call_site).into_error())
+    }
+}
 
 pub struct Interpreter {
-    environment: Environment,
+    environment: EnvRef,
+    call_stack: Vec<Frame>,
+    /// Scope holding the builtins, the parent of every module's top-level scope.
+    builtins: EnvRef,
+    /// The module file being executed, `None` while running the main program.
+    current_file: Option<Rc<str>>,
+    /// Directory the main program's imports are resolved against.
+    script_dir: PathBuf,
+    /// Imported modules by canonical path, so each file runs only once.
+    modules: HashMap<PathBuf, Value>,
+    /// Native modules by name: those the host registered, and the
+    /// built-in ones already imported.
+    native_modules: HashMap<String, Value>,
+    /// The string builtins, looked up by `s.name` member access.
+    string_methods: HashMap<String, Value>,
+    /// Modules whose import is still in progress, outermost first.
+    loading: Vec<PathBuf>,
+    /// The builtin `Error` class that caught errors are instances of.
+    error_class: Rc<Class>,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Interpreter {
@@ -12,228 +125,1382 @@ impl Interpreter {
         let mut env = Environment::new();
         
         // Add built-in functions
-        env.define("print".to_string(), Value::Function {
-            name: "print".to_string(),
-            parameters: vec!["value".to_string()],
-            body: ASTNode::Null(NullNode::new()), // Placeholder for built-in
-        });
+        for (name, function) in get_builtin_functions() {
+            env.define(name.clone(), Value::NativeFunction { name, function: function.into() });
+        }
+        
+        let mut string_methods = HashMap::new();
+        for (name, function) in get_string_functions() {
+            let function = Value::NativeFunction { name: name.clone(), function: function.into() };
+            env.define(name.clone(), function.clone());
+            string_methods.insert(name, function);
+        }
+        
+        for (name, function) in get_context_functions() {
+            env.define(name.clone(), Value::NativeFunction { name, function: function.into() });
+        }
+        
+        for (name, function) in get_ai_ml_functions() {
+            env.define(name.clone(), Value::NativeFunction { name, function: function.into() });
+        }
+        
+        for (name, function) in get_ai_ml_context_functions() {
+            env.define(name.clone(), Value::NativeFunction { name, function: function.into() });
+        }
+        
+        for (name, function) in get_file_system_functions() {
+            env.define(name.clone(), Value::NativeFunction { name, function: function.into() });
+        }
+        
+        for (name, function) in get_image_functions() {
+            env.define(name.clone(), Value::NativeFunction { name, function: function.into() });
+        }
+        
+        let error_class = error_class();
+        env.define("Error".to_string(), Value::Class(Rc::clone(&error_class)));
+        
+        let builtins = env.into_ref();
         
         Self {
-            environment: env,
+            environment: Environment::with_parent(Rc::clone(&builtins)).into_ref(),
+            call_stack: Vec::new(),
+            builtins,
+            current_file: None,
+            script_dir: PathBuf::from("."),
+            modules: HashMap::new(),
+            native_modules: HashMap::new(),
+            string_methods,
+            loading: Vec::new(),
+            error_class,
+        }
+    }
+    
+    /// Sets the file the main program was read from, so its imports
+    /// resolve relative to it.
+    pub fn set_script_path(&mut self, path: impl AsRef<Path>) {
+        if let Some(dir) = path.as_ref().parent() {
+            self.script_dir = dir.to_path_buf();
         }
     }
     
+    /// Makes `import name` bind `value`, usually a `Value::Module` whose
+    /// scope holds native functions. Registered modules take precedence
+    /// over the built-in native modules and over module files.
+    pub fn register_native_module(&mut self, name: impl Into<String>, value: Value) {
+        self.native_modules.insert(name.into(), value);
+    }
+    
     pub fn interpret(&mut self, node: ASTNode) -> Result<Option<Value>> {
-        self.evaluate(node)
+        self.evaluate(&node).map_err(ControlFlow::into_error)
+    }
+    
+    /// Runs one REPL input, reporting its code as coming from `name`. Giving
+    /// each input its own name keeps tracebacks from quoting the current
+    /// input's lines for functions defined in an earlier one.
+    pub fn interpret_input(&mut self, node: ASTNode, name: &str) -> Result<Option<Value>> {
+        let old_file = self.current_file.replace(Rc::from(name));
+        let result = self.interpret(node);
+        self.current_file = old_file;
+        result
     }
     
-    fn evaluate(&mut self, node: ASTNode) -> Result<Option<Value>> {
+    fn evaluate(&mut self, node: &ASTNode) -> EvalResult {
+        let span = node.span();
+        let result = self.evaluate_node(node);
+        
+        match span {
+            Some(span) => result.map_err(|flow| flow.at(span)),
+            None => result,
+        }
+    }
+    
+    fn evaluate_node(&mut self, node: &ASTNode) -> EvalResult {
         match node {
-            ASTNode::Number(n) => Ok(Some(Value::Number(n.value))),
-            ASTNode::String(s) => Ok(Some(Value::String(s.value))),
+            ASTNode::Int(n) => Ok(Some(Value::Int(n.value.clone()))),
+            ASTNode::Float(n) => Ok(Some(Value::Float(n.value))),
+            ASTNode::Imaginary(n) => Ok(Some(Value::Complex(Complex64::new(0.0, n.value)))),
+            ASTNode::String(s) => Ok(Some(Value::String(s.value.clone()))),
+            ASTNode::FString(fstring) => self.evaluate_fstring(fstring),
             ASTNode::Boolean(b) => Ok(Some(Value::Boolean(b.value))),
             ASTNode::Null(_) => Ok(Some(Value::Null)),
             
             ASTNode::Identifier(id) => {
-                let value = self.environment.get(&id.name)?;
+                let value = self.environment.borrow().get(&id.name)?;
                 Ok(Some(value))
             }
             
             ASTNode::BinaryOp(op) => self.evaluate_binary_op(op),
             ASTNode::UnaryOp(op) => self.evaluate_unary_op(op),
             ASTNode::Assignment(assign) => self.evaluate_assignment(assign),
+            ASTNode::Reassignment(assign) => self.evaluate_reassignment(assign),
+            ASTNode::CompoundAssignment(assign) => self.evaluate_compound_assignment(assign),
             ASTNode::Call(call) => self.evaluate_call(call),
             ASTNode::Block(block) => self.evaluate_block(block),
             ASTNode::If(if_node) => self.evaluate_if(if_node),
             ASTNode::While(while_node) => self.evaluate_while(while_node),
+            ASTNode::For(for_node) => self.evaluate_for(for_node),
             ASTNode::Function(func) => self.evaluate_function(func),
+            ASTNode::Lambda(func) => Ok(Some(self.make_function(func, func.name.clone())?)),
             ASTNode::Return(ret) => self.evaluate_return(ret),
+            ASTNode::Break(node) => Err(ControlFlow::Break(node.span)),
+            ASTNode::Continue(node) => Err(ControlFlow::Continue(node.span)),
             ASTNode::List(list) => self.evaluate_list(list),
-            
-            _ => Err(KyaroError::runtime_error("Unsupported AST node", 0, 0)),
+            ASTNode::Dict(dict) => self.evaluate_dict(dict),
+            ASTNode::Index(index) => self.evaluate_index(index),
+            ASTNode::Slice(slice) => self.evaluate_slice(slice),
+            ASTNode::MemberAccess(member) => self.evaluate_member_access(member),
+            ASTNode::Class(class) => self.evaluate_class(class),
+            ASTNode::Import(import) => self.evaluate_import(import),
+            ASTNode::Try(try_node) => self.evaluate_try(try_node),
+            ASTNode::Throw(throw) => self.evaluate_throw(throw),
         }
     }
     
-    fn evaluate_binary_op(&mut self, op: BinaryOpNode) -> Result<Option<Value>> {
-        let left = self.evaluate(*op.left)?.unwrap_or(Value::Null);
-        let right = self.evaluate(*op.right)?.unwrap_or(Value::Null);
+    fn evaluate_fstring(&mut self, fstring: &FStringNode) -> EvalResult {
+        let mut result = String::new();
         
+        for part in &fstring.parts {
+            match part {
+                FStringPart::Text(text) => result.push_str(text),
+                FStringPart::Expression { value, spec, span } => {
+                    let value = self.evaluate(value)?.unwrap_or(Value::Null);
+                    match spec {
+                        Some(spec) => {
+                            let formatted = format_value(&value, spec).map_err(|e| ControlFlow::from(e).at(*span))?;
+                            result.push_str(&formatted);
+                        }
+                        None => result.push_str(&value.to_string()),
+                    }
+                }
+            }
+        }
+        
+        Ok(Some(Value::String(result)))
+    }
+    
+    fn evaluate_binary_op(&mut self, op: &BinaryOpNode) -> EvalResult {
+        let left = self.evaluate(&op.left)?.unwrap_or(Value::Null);
+        
+        // `and`/`or` short-circuit and yield whichever operand decided the result
         match op.operator {
+            TokenType::KeywordAnd if !left.is_truthy() => return Ok(Some(left)),
+            TokenType::KeywordOr if left.is_truthy() => return Ok(Some(left)),
+            TokenType::KeywordAnd | TokenType::KeywordOr => return self.evaluate(&op.right),
+            _ => {}
+        }
+        
+        let right = self.evaluate(&op.right)?.unwrap_or(Value::Null);
+        
+        Ok(Some(self.apply_binary_op(&op.operator, left, right)?))
+    }
+    
+    /// Int operands give an int, except for `/`; if either side is a float
+    /// the int is promoted and the result is a float, and likewise for complex.
+    fn apply_binary_op(&self, operator: &TokenType, left: Value, right: Value) -> Result<Value> {
+        if matches!(left, Value::Complex(_)) || matches!(right, Value::Complex(_)) {
+            if let (Some(a), Some(b)) = (left.as_complex(), right.as_complex()) {
+                match operator {
+                    TokenType::Plus => return Ok(Value::Complex(a + b)),
+                    TokenType::Minus => return Ok(Value::Complex(a - b)),
+                    TokenType::Star => return Ok(Value::Complex(a * b)),
+                    TokenType::Slash if b.is_zero() => return Err(KyaroError::runtime_error("Division by zero", 0, 0)),
+                    TokenType::Slash => return Ok(Value::Complex(complex_divide(a, b))),
+                    TokenType::Power => return complex_pow(a, b).map(Value::Complex),
+                    TokenType::SlashSlash => return Err(self.operand_error("//", &left, &right)),
+                    TokenType::Percent => return Err(self.operand_error("%", &left, &right)),
+                    _ => {}
+                }
+            }
+        }
+        
+        match operator {
             TokenType::Plus => match (left, right) {
-                (Value::Number(a), Value::Number(b)) => Ok(Some(Value::Number(a + b))),
-                (Value::String(a), Value::String(b)) => Ok(Some(Value::String(a + &b))),
-                _ => Err(KyaroError::runtime_error("Invalid operands for +", 0, 0)),
+                (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a + b)),
+                (Value::String(a), Value::String(b)) => Ok(Value::String(a + &b)),
+                (Value::List(a), Value::List(b)) => {
+                    let mut joined = a.borrow().clone();
+                    joined.extend(b.borrow().iter().cloned());
+                    Ok(Value::list(joined))
+                }
+                (a, b) => self.float_op("+", &a, &b, |x, y| Ok(x + y)),
             },
             TokenType::Minus => match (left, right) {
-                (Value::Number(a), Value::Number(b)) => Ok(Some(Value::Number(a - b))),
-                _ => Err(KyaroError::runtime_error("Invalid operands for -", 0, 0)),
+                (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a - b)),
+                (a, b) => self.float_op("-", &a, &b, |x, y| Ok(x - y)),
             },
             TokenType::Star => match (left, right) {
-                (Value::Number(a), Value::Number(b)) => Ok(Some(Value::Number(a * b))),
-                _ => Err(KyaroError::runtime_error("Invalid operands for *", 0, 0)),
+                (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a * b)),
+                (Value::String(s), Value::Int(n)) | (Value::Int(n), Value::String(s)) => {
+                    Ok(Value::String(s.repeat(self.repeat_count(&n)?)))
+                }
+                (Value::List(list), Value::Int(n)) | (Value::Int(n), Value::List(list)) => {
+                    let count = self.repeat_count(&n)?;
+                    let list = list.borrow();
+                    let mut repeated = Vec::with_capacity(list.len() * count);
+                    for _ in 0..count {
+                        repeated.extend(list.iter().cloned());
+                    }
+                    Ok(Value::list(repeated))
+                }
+                (a, b) => self.float_op("*", &a, &b, |x, y| Ok(x * y)),
             },
             TokenType::Slash => match (left, right) {
-                (Value::Number(a), Value::Number(b)) => {
-                    if b == 0.0 {
+                (Value::Int(a), Value::Int(b)) => {
+                    if b.is_zero() {
                         Err(KyaroError::runtime_error("Division by zero", 0, 0))
                     } else {
-                        Ok(Some(Value::Number(a / b)))
+                        Ok(Value::Float(int_true_divide(&a, &b)))
                     }
-                },
-                _ => Err(KyaroError::runtime_error("Invalid operands for /", 0, 0)),
+                }
+                (a, b) => self.float_op("/", &a, &b, |x, y| {
+                    if y == 0.0 {
+                        Err(KyaroError::runtime_error("Division by zero", 0, 0))
+                    } else {
+                        Ok(x / y)
+                    }
+                }),
             },
-            TokenType::EqualsEquals => Ok(Some(Value::Boolean(self.values_equal(&left, &right)))),
-            TokenType::NotEquals => Ok(Some(Value::Boolean(!self.values_equal(&left, &right)))),
-            TokenType::LessThan => match (left, right) {
-                (Value::Number(a), Value::Number(b)) => Ok(Some(Value::Boolean(a < b))),
-                _ => Err(KyaroError::runtime_error("Invalid operands for <", 0, 0)),
+            TokenType::SlashSlash => match (left, right) {
+                (Value::Int(a), Value::Int(b)) => {
+                    if b.is_zero() {
+                        Err(KyaroError::runtime_error("Division by zero", 0, 0))
+                    } else {
+                        Ok(Value::Int(a.div_floor(&b)))
+                    }
+                }
+                (a, b) => self.float_op("//", &a, &b, |x, y| {
+                    if y == 0.0 {
+                        Err(KyaroError::runtime_error("Division by zero", 0, 0))
+                    } else {
+                        Ok((x / y).floor())
+                    }
+                }),
             },
-            TokenType::GreaterThan => match (left, right) {
-                (Value::Number(a), Value::Number(b)) => Ok(Some(Value::Boolean(a > b))),
-                _ => Err(KyaroError::runtime_error("Invalid operands for >", 0, 0)),
+            TokenType::Percent => match (left, right) {
+                // Python semantics: the result takes the sign of the divisor
+                (Value::Int(a), Value::Int(b)) => {
+                    if b.is_zero() {
+                        Err(KyaroError::runtime_error("Modulo by zero", 0, 0))
+                    } else {
+                        Ok(Value::Int(a.mod_floor(&b)))
+                    }
+                }
+                (a, b) => self.float_op("%", &a, &b, |x, y| {
+                    if y == 0.0 {
+                        Err(KyaroError::runtime_error("Modulo by zero", 0, 0))
+                    } else {
+                        Ok(x - y * (x / y).floor())
+                    }
+                }),
             },
-            TokenType::KeywordAnd => Ok(Some(Value::Boolean(left.is_truthy() && right.is_truthy()))),
-            TokenType::KeywordOr => Ok(Some(Value::Boolean(left.is_truthy() || right.is_truthy()))),
+            TokenType::Power => match (left, right) {
+                // A negative exponent makes the result a float
+                (Value::Int(a), Value::Int(b)) if !b.is_negative() => match b.to_u32() {
+                    Some(exponent) => Ok(Value::Int(Pow::pow(a, exponent))),
+                    None => Err(KyaroError::runtime_error(format!("Exponent {} is too large", b), 0, 0)),
+                },
+                (a, b) => match (a.as_f64(), b.as_f64()) {
+                    // A negative base to a fractional power has a complex result
+                    (Some(x), Some(y)) if x < 0.0 && y.is_finite() && y.fract() != 0.0 => {
+                        complex_pow(Complex64::from(x), Complex64::from(y)).map(Value::Complex)
+                    }
+                    _ => self.float_op("**", &a, &b, |x, y| {
+                        if x == 0.0 && y < 0.0 {
+                            Err(KyaroError::runtime_error("Zero cannot be raised to a negative power", 0, 0))
+                        } else {
+                            Ok(x.powf(y))
+                        }
+                    }),
+                },
+            },
+            TokenType::EqualsEquals => Ok(Value::Boolean(left == right)),
+            TokenType::NotEquals => Ok(Value::Boolean(left != right)),
+            TokenType::LessThan => left.compare(&right, "<").map(|o| Value::Boolean(o.is_some_and(Ordering::is_lt))),
+            TokenType::GreaterThan => left.compare(&right, ">").map(|o| Value::Boolean(o.is_some_and(Ordering::is_gt))),
+            TokenType::LessEquals => left.compare(&right, "<=").map(|o| Value::Boolean(o.is_some_and(Ordering::is_le))),
+            TokenType::GreaterEquals => left.compare(&right, ">=").map(|o| Value::Boolean(o.is_some_and(Ordering::is_ge))),
             _ => Err(KyaroError::runtime_error("Unsupported binary operator", 0, 0)),
         }
     }
     
-    fn evaluate_unary_op(&mut self, op: UnaryOpNode) -> Result<Option<Value>> {
-        let operand = self.evaluate(*op.operand)?.unwrap_or(Value::Null);
+    /// Applies an arithmetic operator to two numbers as floats.
+    fn float_op(&self, symbol: &str, left: &Value, right: &Value, op: impl Fn(f64, f64) -> Result<f64>) -> Result<Value> {
+        match (left.as_f64(), right.as_f64()) {
+            (Some(a), Some(b)) => op(a, b).map(Value::Float),
+            _ => Err(self.operand_error(symbol, left, right)),
+        }
+    }
+    
+    fn operand_error(&self, symbol: &str, left: &Value, right: &Value) -> KyaroError {
+        KyaroError::runtime_error(
+            format!("Unsupported operand types for {}: {} and {}", symbol, left.type_name(), right.type_name()),
+            0,
+            0,
+        )
+    }
+    
+    /// Repetition count for `str * n` and `list * n`; negative counts give an empty result.
+    fn repeat_count(&self, n: &BigInt) -> Result<usize> {
+        if n.is_negative() {
+            return Ok(0);
+        }
+        n.to_usize().ok_or_else(|| {
+            KyaroError::runtime_error(format!("Cannot repeat a sequence {} times", n), 0, 0)
+        })
+    }
+    
+    fn evaluate_unary_op(&mut self, op: &UnaryOpNode) -> EvalResult {
+        let operand = self.evaluate(&op.operand)?.unwrap_or(Value::Null);
         
         match op.operator {
             TokenType::Minus => match operand {
-                Value::Number(n) => Ok(Some(Value::Number(-n))),
-                _ => Err(KyaroError::runtime_error("Invalid operand for unary -", 0, 0)),
+                Value::Int(n) => Ok(Some(Value::Int(-n))),
+                Value::Float(n) => Ok(Some(Value::Float(-n))),
+                Value::Complex(n) => Ok(Some(Value::Complex(-n))),
+                _ => Err(KyaroError::runtime_error("Invalid operand for unary -", 0, 0).into()),
             },
             TokenType::KeywordNot => Ok(Some(Value::Boolean(!operand.is_truthy()))),
-            _ => Err(KyaroError::runtime_error("Unsupported unary operator", 0, 0)),
+            _ => Err(KyaroError::runtime_error("Unsupported unary operator", 0, 0).into()),
         }
     }
     
-    fn evaluate_assignment(&mut self, assign: AssignmentNode) -> Result<Option<Value>> {
-        let value = self.evaluate(*assign.value)?.unwrap_or(Value::Null);
-        self.environment.define(assign.name, value.clone());
+    fn evaluate_assignment(&mut self, assign: &AssignmentNode) -> EvalResult {
+        let value = self.evaluate(&assign.value)?.unwrap_or(Value::Null);
+        self.environment.borrow_mut().define(assign.name.clone(), value.clone());
+        Ok(Some(value))
+    }
+    
+    fn evaluate_reassignment(&mut self, assign: &ReassignmentNode) -> EvalResult {
+        let value = self.evaluate(&assign.value)?.unwrap_or(Value::Null);
+        let place = self.evaluate_place(&assign.target)?;
+        self.store(place, value.clone())?;
+        Ok(Some(value))
+    }
+    
+    fn evaluate_compound_assignment(&mut self, assign: &CompoundAssignmentNode) -> EvalResult {
+        let operator = match assign.operator {
+            TokenType::PlusEquals => TokenType::Plus,
+            TokenType::MinusEquals => TokenType::Minus,
+            TokenType::StarEquals => TokenType::Star,
+            TokenType::SlashEquals => TokenType::Slash,
+            TokenType::SlashSlashEquals => TokenType::SlashSlash,
+            TokenType::PercentEquals => TokenType::Percent,
+            TokenType::PowerEquals => TokenType::Power,
+            _ => return Err(KyaroError::runtime_error("Unsupported compound assignment operator", 0, 0).into()),
+        };
+        
+        // The target's object and index are evaluated once, for both the read and the write
+        let place = self.evaluate_place(&assign.target)?;
+        let current = self.load(&place)?;
+        let operand = self.evaluate(&assign.value)?.unwrap_or(Value::Null);
+        let value = self.apply_binary_op(&operator, current, operand)?;
+        
+        self.store(place, value.clone())?;
         Ok(Some(value))
     }
     
-    fn evaluate_call(&mut self, call: CallNode) -> Result<Option<Value>> {
-        let callee = self.evaluate(*call.callee)?.unwrap_or(Value::Null);
+    /// Evaluates the subexpressions of an assignment target.
+    fn evaluate_place(&mut self, target: &ASTNode) -> EvalResult<Place> {
+        match target {
+            ASTNode::Identifier(id) => Ok(Place::Variable(id.name.clone())),
+            ASTNode::Index(index) => {
+                let container = self.evaluate(&index.object)?.unwrap_or(Value::Null);
+                let key = self.evaluate(&index.index)?.unwrap_or(Value::Null);
+                Ok(Place::Item { container, key })
+            }
+            ASTNode::MemberAccess(member) => {
+                let object = self.evaluate(&member.object)?.unwrap_or(Value::Null);
+                Ok(Place::Member { object, name: member.member.clone() })
+            }
+            _ => Err(KyaroError::runtime_error("Invalid assignment target", 0, 0).into()),
+        }
+    }
+    
+    /// Reads the current value of an assignment target.
+    fn load(&self, place: &Place) -> EvalResult<Value> {
+        match place {
+            Place::Variable(name) => Ok(self.environment.borrow().get(name)?),
+            Place::Item { container, key } => self.item(container, key),
+            Place::Member { object, name } => self.member(object, name),
+        }
+    }
+    
+    /// Stores `value` into an assignment target.
+    fn store(&mut self, place: Place, value: Value) -> EvalResult<()> {
+        match place {
+            Place::Variable(name) => Ok(self.environment.borrow_mut().set(&name, value)?),
+            Place::Item { container, key } => match container {
+                Value::List(list) => {
+                    let mut list = list.borrow_mut();
+                    let i = self.list_index(&key, list.len(), "list")?;
+                    list[i] = value;
+                    Ok(())
+                }
+                Value::Dict(dict) => {
+                    dict.borrow_mut().insert(DictKey::from_value(&key)?, value);
+                    Ok(())
+                }
+                other => Err(KyaroError::runtime_error(
+                    format!("'{}' does not support item assignment", other.type_name()),
+                    0,
+                    0,
+                ).into()),
+            },
+            Place::Member { object, name } => match object {
+                Value::Instance(instance) => {
+                    instance.borrow_mut().fields.insert(name, value);
+                    Ok(())
+                }
+                Value::Module(module) => {
+                    module.scope.borrow_mut().define(name, value);
+                    Ok(())
+                }
+                other => Err(KyaroError::runtime_error(
+                    format!("Cannot set member '{}' on type {}", name, other.type_name()),
+                    0,
+                    0,
+                ).into()),
+            },
+        }
+    }
+    
+    fn evaluate_call(&mut self, call: &CallNode) -> EvalResult {
+        let callee = self.evaluate(&call.callee)?.unwrap_or(Value::Null);
         
+        let (arguments, keywords) = self.evaluate_arguments(call)?;
+        self.call_value(callee, arguments, keywords, call.span)
+    }
+    
+    /// Evaluates a call's positional and keyword arguments, in source order.
+    fn evaluate_arguments(&mut self, call: &CallNode) -> EvalResult<(Vec<Value>, KeywordArguments)> {
+        let mut values = Vec::new();
+        for arg in &call.arguments {
+            values.push(self.evaluate(arg)?.unwrap_or(Value::Null));
+        }
+        
+        let mut keywords = Vec::new();
+        for (name, arg) in &call.keyword_arguments {
+            keywords.push((name.clone(), self.evaluate(arg)?.unwrap_or(Value::Null)));
+        }
+        Ok((values, keywords))
+    }
+    
+    fn call_value(
+        &mut self,
+        callee: Value,
+        arguments: Vec<Value>,
+        keywords: KeywordArguments,
+        call_site: Span,
+    ) -> EvalResult {
         match callee {
-            Value::Function { name, parameters, body } => {
-                if name == "print" {
-                    // Built-in print function
-                    if !call.arguments.is_empty() {
-                        let arg = self.evaluate(call.arguments[0].clone())?.unwrap_or(Value::Null);
-                        println!("{}", arg.to_string());
+            Value::NativeFunction { name, function } => {
+                let arguments = native_arguments(&name, arguments, keywords)?;
+                match function {
+                    NativeFn::Plain(function) => Ok(Some(function(&arguments)?)),
+                    NativeFn::WithContext(function) => {
+                        let mut context = NativeCall { interpreter: self, call_site };
+                        Ok(Some(function(&mut context, &arguments)?))
+                    }
+                }
+            }
+            Value::Function { name, parameters, body, closure, file } => {
+                // Fail before the evaluator's own recursion overflows the Rust stack
+                if self.call_stack.len() >= MAX_CALL_DEPTH {
+                    let error = KyaroError::runtime_error("maximum recursion depth exceeded", call_site.line, call_site.column);
+                    return Err(error.with_traceback(&self.call_stack, self.current_file.as_deref()).into());
+                }
+                
+                let bindings = bind_arguments(&name, &parameters, arguments, keywords)?;
+                
+                // The call scope nests inside the scope the function was defined in
+                let mut func_env = Environment::with_parent(closure);
+                
+                // Bind parameters
+                for (param, arg_value) in bindings {
+                    func_env.define(param, arg_value);
+                }
+                
+                // Execute function body in its own frame; native calls don't
+                // get one, their errors already point at the call site
+                self.call_stack.push(Frame::new(name, self.current_file.as_deref(), call_site.line, call_site.column));
+                let old_env = std::mem::replace(&mut self.environment, func_env.into_ref());
+                let old_file = std::mem::replace(&mut self.current_file, file);
+                let result = self.evaluate(&body).map_err(|flow| match flow {
+                    ControlFlow::Error(error) => {
+                        ControlFlow::Error(error.with_traceback(&self.call_stack, self.current_file.as_deref()))
+                    }
+                    other => other,
+                });
+                self.current_file = old_file;
+                self.environment = old_env;
+                self.call_stack.pop();
+                
+                match result {
+                    Ok(_) => Ok(Some(Value::Null)),
+                    Err(ControlFlow::Return(value, _)) => Ok(Some(*value)),
+                    Err(flow) => Err(flow.into_error().into()),
+                }
+            }
+            Value::BoundMethod { receiver, method } => {
+                let mut method_arguments = Vec::with_capacity(arguments.len() + 1);
+                method_arguments.push(*receiver);
+                method_arguments.extend(arguments);
+                self.call_value(*method, method_arguments, keywords, call_site)
+            }
+            Value::Class(class) => {
+                // Calling a class creates an instance and runs its `init`, if any
+                let instance = Rc::new(RefCell::new(Instance::new(Rc::clone(&class))));
+                
+                match class.find_method("init") {
+                    Some(init) => {
+                        let bound = Value::BoundMethod {
+                            receiver: Box::new(Value::Instance(Rc::clone(&instance))),
+                            method: Box::new(init),
+                        };
+                        self.call_value(bound, arguments, keywords, call_site)?;
                     }
-                    Ok(Some(Value::Null))
-                } else {
-                    // User-defined function
-                    if call.arguments.len() != parameters.len() {
+                    None if !arguments.is_empty() || !keywords.is_empty() => {
                         return Err(KyaroError::runtime_error(
-                            format!("Expected {} arguments, got {}", parameters.len(), call.arguments.len()),
+                            format!("{}() takes no arguments", class.name),
                             0,
                             0,
-                        ));
+                        ).into());
                     }
-                    
-                    // Create new environment for function scope
-                    let mut func_env = Environment::with_parent(self.environment.clone());
-                    
-                    // Bind parameters
-                    for (param, arg) in parameters.iter().zip(call.arguments.iter()) {
-                        let arg_value = self.evaluate(arg.clone())?.unwrap_or(Value::Null);
-                        func_env.define(param.clone(), arg_value);
-                    }
-                    
-                    // Execute function body
-                    let old_env = std::mem::replace(&mut self.environment, func_env);
-                    let result = self.evaluate(body);
-                    self.environment = old_env;
-                    
-                    result
+                    None => {}
                 }
+                
+                Ok(Some(Value::Instance(instance)))
             }
-            _ => Err(KyaroError::runtime_error("Not a function", 0, 0)),
+            _ => Err(KyaroError::runtime_error(
+                format!("Cannot call non-function type: {}", callee.type_name()),
+                0,
+                0,
+            ).into()),
         }
     }
     
-    fn evaluate_block(&mut self, block: BlockNode) -> Result<Option<Value>> {
+    fn evaluate_block(&mut self, block: &BlockNode) -> EvalResult {
         let mut last_value = None;
         
-        for stmt in block.statements {
+        for stmt in &block.statements {
             last_value = self.evaluate(stmt)?;
         }
         
         Ok(last_value)
     }
     
-    fn evaluate_if(&mut self, if_node: IfNode) -> Result<Option<Value>> {
-        let condition = self.evaluate(*if_node.condition)?.unwrap_or(Value::Null);
+    fn evaluate_if(&mut self, if_node: &IfNode) -> EvalResult {
+        let condition = self.evaluate(&if_node.condition)?.unwrap_or(Value::Null);
         
         if condition.is_truthy() {
-            self.evaluate(*if_node.then_branch)
-        } else if let Some(else_branch) = if_node.else_branch {
-            self.evaluate(*else_branch)
+            return self.evaluate(&if_node.then_branch);
+        }
+        
+        for (elif_condition, elif_body) in &if_node.elif_branches {
+            let condition = self.evaluate(elif_condition)?.unwrap_or(Value::Null);
+            if condition.is_truthy() {
+                return self.evaluate(elif_body);
+            }
+        }
+        
+        if let Some(else_branch) = &if_node.else_branch {
+            self.evaluate(else_branch)
         } else {
             Ok(Some(Value::Null))
         }
     }
     
-    fn evaluate_while(&mut self, while_node: WhileNode) -> Result<Option<Value>> {
+    fn evaluate_while(&mut self, while_node: &WhileNode) -> EvalResult {
         let mut last_value = None;
         
         loop {
-            let condition = self.evaluate((*while_node.condition).clone())?.unwrap_or(Value::Null);
+            let condition = self.evaluate(&while_node.condition)?.unwrap_or(Value::Null);
             if !condition.is_truthy() {
                 break;
             }
             
-            last_value = self.evaluate((*while_node.body).clone())?;
+            match self.evaluate(&while_node.body) {
+                Ok(value) => last_value = value,
+                Err(ControlFlow::Break(_)) => break,
+                Err(ControlFlow::Continue(_)) => continue,
+                Err(other) => return Err(other),
+            }
         }
         
         Ok(last_value)
     }
     
-    fn evaluate_function(&mut self, func: FunctionNode) -> Result<Option<Value>> {
-        let function_value = Value::Function {
-            name: func.name.clone(),
-            parameters: func.parameters,
-            body: *func.body,
+    fn evaluate_for(&mut self, for_node: &ForNode) -> EvalResult {
+        let items = self.iterate(&for_node.iterable)?;
+        
+        // The loop variable lives in its own scope nested in the current one
+        let loop_env = Environment::with_parent(Rc::clone(&self.environment)).into_ref();
+        let outer = std::mem::replace(&mut self.environment, loop_env);
+        
+        let mut result = Ok(None);
+        for item in items {
+            self.environment.borrow_mut().define(for_node.variable.clone(), item);
+            
+            match self.evaluate(&for_node.body) {
+                Ok(value) => result = Ok(value),
+                Err(ControlFlow::Break(_)) => break,
+                Err(ControlFlow::Continue(_)) => continue,
+                Err(other) => {
+                    result = Err(other);
+                    break;
+                }
+            }
+        }
+        
+        self.environment = outer;
+        
+        result
+    }
+    
+    /// Runs the try block, hands an error it raises to the catch block, and
+    /// always runs the finally block. Return, break and continue pass through.
+    fn evaluate_try(&mut self, try_node: &TryNode) -> EvalResult {
+        let mut result = self.evaluate(&try_node.body);
+        
+        if let Some(catch_body) = &try_node.catch_body {
+            result = match result {
+                Err(ControlFlow::Error(error)) => {
+                    // The caught error is bound in its own scope, like a loop variable
+                    let catch_env = Environment::with_parent(Rc::clone(&self.environment)).into_ref();
+                    if let Some(variable) = &try_node.catch_variable {
+                        catch_env.borrow_mut().define(variable.clone(), self.error_value(&error));
+                    }
+                    
+                    let outer = std::mem::replace(&mut self.environment, catch_env);
+                    let result = self.evaluate(catch_body);
+                    self.environment = outer;
+                    result
+                }
+                other => other,
+            };
+        }
+        
+        // An error or exit from the finally block replaces the pending result
+        if let Some(finally_body) = &try_node.finally_body {
+            self.evaluate(finally_body)?;
+        }
+        
+        result
+    }
+    
+    fn evaluate_throw(&mut self, throw: &ThrowNode) -> EvalResult {
+        let value = self.evaluate(&throw.value)?.unwrap_or(Value::Null);
+        Err(self.thrown_error(value).into())
+    }
+    
+    /// The value a catch block receives for an error. A thrown error is
+    /// its own `Error` instance, which learns its position the first time
+    /// it is thrown; other errors are wrapped in a new instance.
+    fn error_value(&self, error: &KyaroError) -> Value {
+        let (line, column) = error.position();
+        
+        if let KyaroError::UserError { value, .. } = error {
+            if let Value::Instance(instance) = value.as_ref() {
+                let fields = &mut instance.borrow_mut().fields;
+                if fields.get("line").is_none_or(|line| *line == Value::int(0)) {
+                    fields.insert("line".to_string(), Value::int(line));
+                    fields.insert("column".to_string(), Value::int(column));
+                }
+                // Subclasses that skip `Error.init` still get every field
+                fields.entry("message".to_string()).or_insert_with(|| Value::String(error.message().to_string()));
+                fields.entry("kind".to_string()).or_insert_with(|| Value::String(error.kind().to_string()));
+            }
+            return value.as_ref().clone();
+        }
+        
+        self.error_instance(error.message(), error.kind(), line, column)
+    }
+    
+    fn error_instance(&self, message: &str, kind: &str, line: usize, column: usize) -> Value {
+        let mut instance = Instance::new(Rc::clone(&self.error_class));
+        instance.fields.insert("message".to_string(), Value::String(message.to_string()));
+        instance.fields.insert("kind".to_string(), Value::String(kind.to_string()));
+        instance.fields.insert("line".to_string(), Value::int(line));
+        instance.fields.insert("column".to_string(), Value::int(column));
+        Value::Instance(Rc::new(RefCell::new(instance)))
+    }
+    
+    /// Turns a thrown value into an error. Instances of `Error` travel with
+    /// the error, keeping their kind and position, so a caught error can be
+    /// rethrown unchanged; any other value is wrapped in a user error with
+    /// the value as its message.
+    fn thrown_error(&self, value: Value) -> KyaroError {
+        let instance = match value {
+            Value::Instance(instance) if instance.borrow().class.inherits_from(&self.error_class) => instance,
+            other => {
+                let message = match other {
+                    Value::String(message) => message,
+                    other => other.to_string(),
+                };
+                let error = self.error_instance(&message, "user", 0, 0);
+                return KyaroError::user_error(error, message, 0, 0);
+            }
+        };
+        
+        let fields = &instance.borrow().fields;
+        let message = match fields.get("message") {
+            Some(Value::String(message)) => message.clone(),
+            Some(other) => other.to_string(),
+            None => String::new(),
+        };
+        let position = |name: &str| match fields.get(name) {
+            Some(Value::Int(n)) => n.to_usize().unwrap_or(0),
+            _ => 0,
+        };
+        let (line, column) = (position("line"), position("column"));
+        
+        match fields.get("kind") {
+            Some(Value::String(kind)) if kind == "lexer" => KyaroError::lexer_error(message, line, column),
+            Some(Value::String(kind)) if kind == "parser" => KyaroError::parser_error(message, line, column),
+            Some(Value::String(kind)) if kind == "runtime" => KyaroError::runtime_error(message, line, column),
+            _ => KyaroError::user_error(Value::Instance(Rc::clone(&instance)), message, line, column),
+        }
+    }
+    
+    /// Produces the items a `for` loop walks over. A direct call to the
+    /// builtin `range` is iterated lazily instead of building a list.
+    fn iterate(&mut self, iterable: &ASTNode) -> EvalResult<Box<dyn Iterator<Item = Value>>> {
+        let value = match iterable {
+            ASTNode::Call(call) => {
+                let callee = self.evaluate(&call.callee)?.unwrap_or(Value::Null);
+                let (arguments, keywords) = self.evaluate_arguments(call)?;
+                
+                if let Value::NativeFunction { ref name, .. } = callee {
+                    if name == "range" && keywords.is_empty() {
+                        let range = range_iter(&arguments).map_err(|error| ControlFlow::from(error).at(call.span))?;
+                        return Ok(Box::new(range));
+                    }
+                }
+                
+                self.call_value(callee, arguments, keywords, call.span)
+                    .map_err(|flow| flow.at(call.span))?
+                    .unwrap_or(Value::Null)
+            }
+            other => self.evaluate(other)?.unwrap_or(Value::Null),
         };
         
-        self.environment.define(func.name, function_value.clone());
+        match value {
+            // Iterate over a snapshot so the body may modify the list
+            Value::List(list) => Ok(Box::new(list.borrow().clone().into_iter())),
+            Value::Dict(dict) => {
+                let keys: Vec<Value> = dict.borrow().keys().map(DictKey::to_value).collect();
+                Ok(Box::new(keys.into_iter()))
+            }
+            Value::String(s) => {
+                let chars: Vec<Value> = s.chars().map(|c| Value::String(c.to_string())).collect();
+                Ok(Box::new(chars.into_iter()))
+            }
+            other => Err(KyaroError::runtime_error(
+                format!("Cannot iterate over type {}", other.type_name()),
+                0,
+                0,
+            ).into()),
+        }
+    }
+    
+    fn evaluate_function(&mut self, func: &FunctionNode) -> EvalResult {
+        let function_value = self.make_function(func, func.name.clone())?;
+        
+        self.environment.borrow_mut().define(func.name.clone(), function_value.clone());
         Ok(Some(function_value))
     }
     
-    fn evaluate_return(&mut self, ret: ReturnNode) -> Result<Option<Value>> {
-        if let Some(value) = ret.value {
-            self.evaluate(*value)
+    /// Creates a function value closing over the current scope. Default
+    /// parameter values are evaluated here, once.
+    fn make_function(&mut self, func: &FunctionNode, name: String) -> EvalResult<Value> {
+        let list = &func.parameters;
+        let mut defaults = Vec::with_capacity(list.parameters.len());
+        for parameter in &list.parameters {
+            defaults.push(match &parameter.default {
+                Some(default) => Some(self.evaluate(default)?.unwrap_or(Value::Null)),
+                None => None,
+            });
+        }
+        
+        let parameters = Parameters {
+            names: list.parameters.iter().map(|parameter| parameter.name.clone()).collect(),
+            defaults,
+            positional: list.positional,
+            rest: list.rest.clone(),
+            keywords: list.keywords.clone(),
+        };
+        
+        Ok(Value::Function {
+            name,
+            parameters: Rc::new(parameters),
+            body: Rc::clone(&func.body),
+            closure: Rc::clone(&self.environment),
+            file: self.current_file.clone(),
+        })
+    }
+    
+    fn evaluate_class(&mut self, class: &ClassNode) -> EvalResult {
+        let superclass = match &class.superclass {
+            Some(node) => match self.evaluate(node)?.unwrap_or(Value::Null) {
+                Value::Class(superclass) => Some(superclass),
+                other => {
+                    return Err(KyaroError::runtime_error(
+                        format!("Superclass of {} must be a class, not {}", class.name, other.type_name()),
+                        class.span.line,
+                        class.span.column,
+                    ).into())
+                }
+            },
+            None => None,
+        };
+        
+        // Methods are named `Class.method` so tracebacks show where they live
+        let mut methods = HashMap::new();
+        for method in &class.methods {
+            let name = format!("{}.{}", class.name, method.name);
+            methods.insert(method.name.clone(), self.make_function(method, name)?);
+        }
+        
+        let class_value = Value::Class(Rc::new(Class {
+            name: class.name.clone(),
+            superclass,
+            methods,
+        }));
+        
+        self.environment.borrow_mut().define(class.name.clone(), class_value.clone());
+        Ok(Some(class_value))
+    }
+    
+    /// Binds an imported module under its name, running its file the
+    /// first time it is imported.
+    fn evaluate_import(&mut self, import: &ImportNode) -> EvalResult {
+        // Native modules take precedence over files of the same name
+        if !import.is_path {
+            if let Some(module) = self.native_module(&import.module) {
+                self.environment.borrow_mut().define(import.module.clone(), module.clone());
+                return Ok(Some(module));
+            }
+        }
+        
+        let path = self.resolve_module(import)?;
+        let name = path.file_stem().map_or_else(|| import.module.clone(), |stem| stem.to_string_lossy().into_owned());
+        let key = path.canonicalize().unwrap_or_else(|_| path.clone());
+        
+        let module = match self.modules.get(&key) {
+            Some(module) => module.clone(),
+            None => {
+                let module = self.load_module(&path, &key, name.clone(), import.span)?;
+                self.modules.insert(key, module.clone());
+                module
+            }
+        };
+        
+        self.environment.borrow_mut().define(name, module.clone());
+        Ok(Some(module))
+    }
+    
+    fn native_module(&mut self, name: &str) -> Option<Value> {
+        if let Some(module) = self.native_modules.get(name) {
+            return Some(module.clone());
+        }
+        
+        let scope = get_native_module(name)?;
+        let module = Value::Module(Rc::new(Module {
+            name: name.to_string(),
+            scope: scope.into_ref(),
+        }));
+        self.native_modules.insert(name.to_string(), module.clone());
+        Some(module)
+    }
+    
+    /// Finds the file an import refers to: relative to the importing file,
+    /// then in each directory of `KYARO_PATH`.
+    fn resolve_module(&self, import: &ImportNode) -> Result<PathBuf> {
+        let file_name = if import.is_path {
+            PathBuf::from(&import.module)
         } else {
-            Ok(Some(Value::Null))
+            PathBuf::from(format!("{}.kyaro", import.module))
+        };
+        
+        if file_name.is_absolute() {
+            return if file_name.is_file() {
+                Ok(file_name)
+            } else {
+                Err(KyaroError::runtime_error(format!("Cannot find module '{}'", import.module), 0, 0))
+            };
         }
+        
+        let base_dir = match &self.current_file {
+            Some(file) => Path::new(file.as_ref()).parent().map_or_else(PathBuf::new, Path::to_path_buf),
+            None => self.script_dir.clone(),
+        };
+        
+        let search_path = std::env::var_os("KYARO_PATH")
+            .map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+            .unwrap_or_default();
+        
+        std::iter::once(base_dir)
+            .chain(search_path)
+            .map(|dir| dir.join(&file_name))
+            .find(|candidate| candidate.is_file())
+            .ok_or_else(|| KyaroError::runtime_error(format!("Cannot find module '{}'", import.module), 0, 0))
+    }
+    
+    /// Runs a module file in a fresh top-level scope and wraps that scope as a module value.
+    fn load_module(&mut self, path: &Path, key: &Path, name: String, import_site: Span) -> EvalResult<Value> {
+        if let Some(start) = self.loading.iter().position(|loading| loading == key) {
+            let cycle: Vec<String> = self.loading[start..].iter()
+                .chain(std::iter::once(&key.to_path_buf()))
+                .map(|path| path.display().to_string())
+                .collect();
+            return Err(KyaroError::runtime_error(
+                format!("Circular import: {}", cycle.join(" -> ")),
+                0,
+                0,
+            ).into());
+        }
+        
+        let display_path = path.display().to_string();
+        let source = std::fs::read_to_string(path).map_err(|e| {
+            KyaroError::runtime_error(format!("Cannot read module '{}': {}", display_path, e), 0, 0)
+        })?;
+        // Syntax errors keep their kind so they can be caught as such
+        let ast = Lexer::new(&source)
+            .tokenize()
+            .and_then(|tokens| Parser::new(tokens).parse())
+            .map_err(|e| match e {
+                KyaroError::LexerError { message, line, column } => {
+                    KyaroError::lexer_error(format!("In module '{}': {}", display_path, message), line, column)
+                }
+                KyaroError::ParserError { message, line, column } => {
+                    KyaroError::parser_error(format!("In module '{}': {}", display_path, message), line, column)
+                }
+                other => KyaroError::runtime_error(format!("In module '{}': {}", display_path, other), 0, 0),
+            })?;
+        
+        let scope = Environment::with_parent(Rc::clone(&self.builtins)).into_ref();
+        
+        self.call_stack.push(Frame::new("<module>", self.current_file.as_deref(), import_site.line, import_site.column));
+        self.loading.push(key.to_path_buf());
+        let old_env = std::mem::replace(&mut self.environment, Rc::clone(&scope));
+        let old_file = self.current_file.replace(Rc::from(display_path.as_str()));
+        
+        let result = self.evaluate(&ast).map_err(|flow| {
+            let error = flow.into_error().with_traceback(&self.call_stack, self.current_file.as_deref());
+            ControlFlow::Error(error)
+        });
+        
+        self.current_file = old_file;
+        self.environment = old_env;
+        self.loading.pop();
+        self.call_stack.pop();
+        
+        result?;
+        Ok(Value::Module(Rc::new(Module { name, scope })))
     }
     
-    fn evaluate_list(&mut self, list: ListNode) -> Result<Option<Value>> {
+    /// Reads a field or method from an instance, or an unbound method from a class.
+    fn evaluate_member_access(&mut self, member: &MemberAccessNode) -> EvalResult {
+        let object = self.evaluate(&member.object)?.unwrap_or(Value::Null);
+        self.member(&object, &member.member).map(Some)
+    }
+    
+    /// `object.name` for an already evaluated object.
+    fn member(&self, object: &Value, name: &str) -> EvalResult<Value> {
+        match object {
+            Value::Instance(instance) => {
+                if let Some(value) = instance.borrow().fields.get(name) {
+                    return Ok(value.clone());
+                }
+                
+                let method = instance.borrow().class.find_method(name);
+                match method {
+                    Some(method) => Ok(Value::BoundMethod {
+                        receiver: Box::new(object.clone()),
+                        method: Box::new(method),
+                    }),
+                    None => Err(KyaroError::runtime_error(
+                        format!("'{}' object has no attribute '{}'", instance.borrow().class.name, name),
+                        0,
+                        0,
+                    ).into()),
+                }
+            }
+            Value::Module(module) => match module.scope.borrow().get_local(name) {
+                Some(value) => Ok(value),
+                None => Err(KyaroError::runtime_error(
+                    format!("Module '{}' has no attribute '{}'", module.name, name),
+                    0,
+                    0,
+                ).into()),
+            },
+            Value::Class(class) => match class.find_method(name) {
+                Some(method) => Ok(method),
+                None => Err(KyaroError::runtime_error(
+                    format!("Class {} has no method '{}'", class.name, name),
+                    0,
+                    0,
+                ).into()),
+            },
+            Value::String(_) if self.string_methods.contains_key(name) => Ok(Value::BoundMethod {
+                receiver: Box::new(object.clone()),
+                method: Box::new(self.string_methods[name].clone()),
+            }),
+            other => Err(KyaroError::runtime_error(
+                format!("Type {} has no member '{}'", other.type_name(), name),
+                0,
+                0,
+            ).into()),
+        }
+    }
+    
+    fn evaluate_return(&mut self, ret: &ReturnNode) -> EvalResult {
+        let value = match &ret.value {
+            Some(value) => self.evaluate(value)?.unwrap_or(Value::Null),
+            None => Value::Null,
+        };
+        
+        Err(ControlFlow::Return(Box::new(value), ret.span))
+    }
+    
+    fn evaluate_list(&mut self, list: &ListNode) -> EvalResult {
         let mut values = Vec::new();
         
-        for element in list.elements {
+        for element in &list.elements {
             let value = self.evaluate(element)?.unwrap_or(Value::Null);
             values.push(value);
         }
         
-        Ok(Some(Value::List(values)))
+        Ok(Some(Value::list(values)))
+    }
+    
+    fn evaluate_dict(&mut self, dict: &DictNode) -> EvalResult {
+        let mut entries = IndexMap::new();
+        
+        for (key, value) in &dict.entries {
+            let key = self.evaluate(key)?.unwrap_or(Value::Null);
+            let value = self.evaluate(value)?.unwrap_or(Value::Null);
+            entries.insert(DictKey::from_value(&key)?, value);
+        }
+        
+        Ok(Some(Value::dict(entries)))
+    }
+    
+    fn evaluate_index(&mut self, index: &IndexNode) -> EvalResult {
+        let object = self.evaluate(&index.object)?.unwrap_or(Value::Null);
+        let key = self.evaluate(&index.index)?.unwrap_or(Value::Null);
+        self.item(&object, &key).map(Some)
+    }
+    
+    /// `object[key]` for an already evaluated object and key.
+    fn item(&self, object: &Value, key: &Value) -> EvalResult<Value> {
+        match object {
+            Value::List(list) => {
+                let list = list.borrow();
+                let i = self.list_index(key, list.len(), "list")?;
+                Ok(list[i].clone())
+            }
+            Value::Dict(dict) => match dict.borrow().get(&DictKey::from_value(key)?) {
+                Some(value) => Ok(value.clone()),
+                None => Err(KyaroError::runtime_error(
+                    format!("Key {} not found in dict", key.repr()),
+                    0,
+                    0,
+                ).into()),
+            },
+            Value::String(s) => {
+                let chars: Vec<char> = s.chars().collect();
+                let i = self.list_index(key, chars.len(), "string")?;
+                Ok(Value::String(chars[i].to_string()))
+            }
+            other => Err(KyaroError::runtime_error(
+                format!("Cannot index type {}", other.type_name()),
+                0,
+                0,
+            ).into()),
+        }
+    }
+    
+    fn evaluate_slice(&mut self, slice: &SliceNode) -> EvalResult {
+        let object = self.evaluate(&slice.object)?.unwrap_or(Value::Null);
+        let start = self.evaluate_slice_bound(&slice.start)?;
+        let end = self.evaluate_slice_bound(&slice.end)?;
+        let step = self.evaluate_slice_bound(&slice.step)?.unwrap_or(1);
+        
+        if step == 0 {
+            return Err(KyaroError::runtime_error("Slice step cannot be zero", 0, 0).into());
+        }
+        
+        match object {
+            Value::List(list) => {
+                let list = list.borrow();
+                let indices = slice_indices(list.len(), start, end, step);
+                Ok(Some(Value::list(indices.into_iter().map(|i| list[i].clone()).collect())))
+            }
+            Value::String(s) => {
+                let chars: Vec<char> = s.chars().collect();
+                let indices = slice_indices(chars.len(), start, end, step);
+                Ok(Some(Value::String(indices.into_iter().map(|i| chars[i]).collect())))
+            }
+            other => Err(KyaroError::runtime_error(
+                format!("Cannot slice type {}", other.type_name()),
+                0,
+                0,
+            ).into()),
+        }
+    }
+    
+    fn evaluate_slice_bound(&mut self, bound: &Option<Box<ASTNode>>) -> EvalResult<Option<i64>> {
+        match bound {
+            Some(node) => match self.evaluate(node)?.unwrap_or(Value::Null) {
+                Value::Null => Ok(None),
+                value => Ok(Some(self.integer_index(&value)?)),
+            },
+            None => Ok(None),
+        }
+    }
+    
+    /// Resolves a Python-style index, where negative values count from the end.
+    fn list_index(&self, key: &Value, len: usize, type_name: &str) -> Result<usize> {
+        let index = self.integer_index(key)?;
+        let resolved = if index < 0 { index + len as i64 } else { index };
+        
+        if resolved < 0 || resolved >= len as i64 {
+            return Err(KyaroError::runtime_error(
+                format!("{} index {} out of range (length {})", type_name, index, len),
+                0,
+                0,
+            ));
+        }
+        
+        Ok(resolved as usize)
     }
     
-    fn values_equal(&self, a: &Value, b: &Value) -> bool {
-        match (a, b) {
-            (Value::Number(a), Value::Number(b)) => a == b,
-            (Value::String(a), Value::String(b)) => a == b,
-            (Value::Boolean(a), Value::Boolean(b)) => a == b,
-            (Value::Null, Value::Null) => true,
-            _ => false,
+    fn integer_index(&self, key: &Value) -> Result<i64> {
+        match key {
+            Value::Int(n) => n.to_i64().ok_or_else(|| {
+                KyaroError::runtime_error(format!("Index {} is too large", n), 0, 0)
+            }),
+            other => Err(KyaroError::runtime_error(
+                format!("Indices must be integers, not {}", other.type_name()),
+                0,
+                0,
+            )),
         }
     }
 }
+
+/// `a / b` for ints, keeping the quotient's integer part exact so large
+/// operands are not rounded before dividing.
+fn int_true_divide(a: &BigInt, b: &BigInt) -> f64 {
+    let (quotient, remainder) = a.div_rem(b);
+    // Scale the remainder and divisor down together so both fit in a float
+    let shift = b.bits().saturating_sub(1000);
+    let fraction = (remainder >> shift).to_f64().unwrap_or(0.0) / (b >> shift).to_f64().unwrap_or(f64::INFINITY);
+    quotient.to_f64().unwrap_or(f64::INFINITY) + fraction
+}
+
+/// Computes the element positions selected by a slice, clamping the bounds
+/// the same way Python does.
+fn slice_indices(len: usize, start: Option<i64>, end: Option<i64>, step: i64) -> Vec<usize> {
+    let len = len as i64;
+    let resolve = |bound: i64, lower: i64, upper: i64| {
+        let bound = if bound < 0 { bound + len } else { bound };
+        bound.clamp(lower, upper)
+    };
+    
+    let mut indices = Vec::new();
+    if step > 0 {
+        let mut i = start.map_or(0, |s| resolve(s, 0, len));
+        let stop = end.map_or(len, |e| resolve(e, 0, len));
+        while i < stop {
+            indices.push(i as usize);
+            // A step past the end of `i64` ends the slice
+            match i.checked_add(step) {
+                Some(next) => i = next,
+                None => break,
+            }
+        }
+    } else {
+        let mut i = start.map_or(len - 1, |s| resolve(s, -1, len - 1));
+        let stop = end.map_or(-1, |e| resolve(e, -1, len - 1));
+        while i > stop {
+            indices.push(i as usize);
+            // A step past the end of `i64` ends the slice
+            match i.checked_add(step) {
+                Some(next) => i = next,
+                None => break,
+            }
+        }
+    }
+    
+    indices
+}
+
+/// Matches call arguments to a function's parameters and returns the
+/// values to bind, including the `*rest` list and `**keywords` dict.
+fn bind_arguments(
+    name: &str,
+    parameters: &Parameters,
+    arguments: Vec<Value>,
+    keywords: KeywordArguments,
+) -> Result<Vec<(String, Value)>> {
+    let given = arguments.len();
+    let by_position = keywords.is_empty();
+    let mut slots: Vec<Option<Value>> = vec![None; parameters.names.len()];
+    
+    let mut arguments = arguments.into_iter();
+    for slot in slots.iter_mut().take(parameters.positional) {
+        *slot = arguments.next();
+    }
+    let rest: Vec<Value> = arguments.collect();
+    if !rest.is_empty() && parameters.rest.is_none() {
+        return Err(arity_error(name, parameters, given));
+    }
+    
+    let mut extra = IndexMap::new();
+    for (keyword, value) in keywords {
+        match parameters.names.iter().position(|parameter| *parameter == keyword) {
+            Some(index) if slots[index].is_some() => {
+                return Err(KyaroError::runtime_error(
+                    format!("Function {} got multiple values for argument '{}'", name, keyword),
+                    0,
+                    0,
+                ));
+            }
+            Some(index) => slots[index] = Some(value),
+            None if parameters.keywords.is_some() => {
+                extra.insert(DictKey::String(keyword), value);
+            }
+            None => {
+                return Err(KyaroError::runtime_error(
+                    format!("Function {} got an unexpected keyword argument '{}'", name, keyword),
+                    0,
+                    0,
+                ));
+            }
+        }
+    }
+    
+    let mut bindings = Vec::with_capacity(slots.len() + 2);
+    for (index, (slot, default)) in slots.into_iter().zip(&parameters.defaults).enumerate() {
+        let value = match slot.or_else(|| default.clone()) {
+            Some(value) => value,
+            None if by_position && index < parameters.positional => return Err(arity_error(name, parameters, given)),
+            None => {
+                return Err(KyaroError::runtime_error(
+                    format!("Function {} missing argument '{}'", name, parameters.names[index]),
+                    0,
+                    0,
+                ));
+            }
+        };
+        bindings.push((parameters.names[index].clone(), value));
+    }
+    
+    if let Some(rest_name) = &parameters.rest {
+        bindings.push((rest_name.clone(), Value::list(rest)));
+    }
+    if let Some(keywords_name) = &parameters.keywords {
+        bindings.push((keywords_name.clone(), Value::dict(extra)));
+    }
+    Ok(bindings)
+}
+
+fn arity_error(name: &str, parameters: &Parameters, given: usize) -> KyaroError {
+    let positional = &parameters.defaults[..parameters.positional];
+    let required = positional.iter().filter(|default| default.is_none()).count();
+    
+    let (expected, most) = if parameters.rest.is_some() {
+        (format!("at least {}", required), required)
+    } else if required == positional.len() {
+        (required.to_string(), required)
+    } else {
+        (format!("{} to {}", required, positional.len()), positional.len())
+    };
+    let plural = if most == 1 { "argument" } else { "arguments" };
+    KyaroError::runtime_error(format!("Function {} expects {} {}, got {}", name, expected, plural, given), 0, 0)
+}
+
+/// Native functions take positional arguments only, so keyword arguments
+/// are placed by the parameter names the native's module declares.
+/// Optional parameters skipped before a keyword argument are passed as
+/// null, which natives treat as not given; skipping a required one is an error.
+fn native_arguments(name: &str, arguments: Vec<Value>, keywords: KeywordArguments) -> Result<Vec<Value>> {
+    if keywords.is_empty() {
+        return Ok(arguments);
+    }
+    
+    let parameters = builtin_parameters(name)
+        .or_else(|| ai_ml_parameters(name))
+        .or_else(|| file_system_parameters(name))
+        .or_else(|| image_parameters(name))
+        .ok_or_else(|| KyaroError::runtime_error(format!("{}() does not accept keyword arguments", name), 0, 0))?;
+    
+    let mut slots: Vec<Option<Value>> = arguments.into_iter().map(Some).collect();
+    for (keyword, value) in keywords {
+        let index = parameters.iter().position(|parameter| parameter.trim_end_matches('?') == keyword).ok_or_else(|| {
+            KyaroError::runtime_error(format!("{}() got an unexpected keyword argument '{}'", name, keyword), 0, 0)
+        })?;
+        
+        if slots.len() <= index {
+            slots.resize_with(index + 1, || None);
+        }
+        if slots[index].is_some() {
+            return Err(KyaroError::runtime_error(
+                format!("{}() got multiple values for argument '{}'", name, keyword),
+                0,
+                0,
+            ));
+        }
+        slots[index] = Some(value);
+    }
+    
+    slots
+        .into_iter()
+        .enumerate()
+        .map(|(index, slot)| match slot {
+            Some(value) => Ok(value),
+            None if parameters[index].ends_with('?') => Ok(Value::Null),
+            None => Err(KyaroError::runtime_error(format!("{}() missing argument '{}'", name, parameters[index]), 0, 0)),
+        })
+        .collect()
+}
+
+/// The builtin `Error` class. `Error(message)` creates a user error that
+/// `throw` raises with the position of the throw statement.
+fn error_class() -> Rc<Class> {
+    let mut methods = HashMap::new();
+    methods.insert(
+        "init".to_string(),
+        Value::NativeFunction {
+            name: "Error.init".to_string(),
+            function: NativeFn::Plain(error_init),
+        },
+    );
+    
+    Rc::new(Class {
+        name: "Error".to_string(),
+        superclass: None,
+        methods,
+    })
+}
+
+fn error_init(args: &[Value]) -> Result<Value> {
+    let (Some(Value::Instance(instance)), 1..=2) = (args.first(), args.len()) else {
+        return Err(KyaroError::runtime_error("Error() takes at most 1 argument".to_string(), 0, 0));
+    };
+    
+    let message = match args.get(1) {
+        Some(Value::String(message)) => message.clone(),
+        Some(other) => other.to_string(),
+        None => String::new(),
+    };
+    
+    let fields = &mut instance.borrow_mut().fields;
+    fields.insert("message".to_string(), Value::String(message));
+    fields.insert("kind".to_string(), Value::String("user".to_string()));
+    fields.insert("line".to_string(), Value::int(0));
+    fields.insert("column".to_string(), Value::int(0));
+    Ok(Value::Null)
+}
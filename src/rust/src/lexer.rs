@@ -1,5 +1,6 @@
 use crate::errors::{KyaroError, Result};
-use crate::token_types::{Token, TokenType, TokenValue, create_keywords_map};
+use crate::token_types::{FStringSegment, Token, TokenType, TokenValue, create_keywords_map};
+use num_bigint::BigInt;
 use std::collections::HashMap;
 
 pub struct Lexer {
@@ -14,7 +15,7 @@ pub struct Lexer {
 impl Lexer {
     pub fn new(source: &str) -> Self {
         let chars: Vec<char> = source.chars().collect();
-        let current_char = chars.get(0).copied();
+        let current_char = chars.first().copied();
         
         Self {
             source: chars,
@@ -26,6 +27,15 @@ impl Lexer {
         }
     }
     
+    /// A lexer for source embedded in a larger file at `line` and `column`,
+    /// so its tokens carry positions in that file.
+    fn embedded(source: &str, line: usize, column: usize) -> Self {
+        let mut lexer = Self::new(source);
+        lexer.line = line;
+        lexer.column = column;
+        lexer
+    }
+    
     fn advance(&mut self) {
         if self.current_char == Some('\n') {
             self.line += 1;
@@ -72,60 +82,246 @@ impl Lexer {
         }
     }
     
+    /// Reads a number literal: decimal with an optional fraction and
+    /// exponent, or hexadecimal, binary or octal after `0x`, `0b` or `0o`.
+    /// Single underscores may separate digits. Literals without a fraction
+    /// or exponent are integers and keep every digit, and a decimal literal
+    /// ending in `j` is imaginary.
     fn read_number(&mut self) -> Result<Token> {
         let start_line = self.line;
         let start_column = self.column;
+        
+        let radix = match (self.current_char, self.peek(1)) {
+            (Some('0'), Some('x' | 'X')) => Some((16, "hexadecimal")),
+            (Some('0'), Some('b' | 'B')) => Some((2, "binary")),
+            (Some('0'), Some('o' | 'O')) => Some((8, "octal")),
+            _ => None,
+        };
+        
+        let value = match radix {
+            Some((radix, name)) => self.read_radix_number(radix, name)?,
+            None => self.read_decimal_number()?,
+        };
+        
+        // A literal may not run straight into a name, as in `12abc`
+        if let Some(ch) = self.current_char {
+            if ch.is_alphanumeric() || ch == '_' {
+                return Err(KyaroError::lexer_error(
+                    format!("Invalid character '{}' in number literal", ch),
+                    self.line,
+                    self.column,
+                ));
+            }
+        }
+        
+        Ok(Token::new(TokenType::Number, value, start_line, start_column))
+    }
+    
+    fn read_decimal_number(&mut self) -> Result<TokenValue> {
+        let start_line = self.line;
+        let start_column = self.column;
         let mut num_str = String::new();
-        let mut has_dot = false;
         
-        while let Some(ch) = self.current_char {
-            if ch.is_ascii_digit() {
-                num_str.push(ch);
-                self.advance();
-            } else if ch == '.' && !has_dot {
-                has_dot = true;
-                num_str.push(ch);
+        self.read_digits(10, &mut num_str, false)?;
+        let mut is_float = false;
+        
+        if self.current_char == Some('.') {
+            is_float = true;
+            num_str.push('.');
+            self.advance();
+            self.read_digits(10, &mut num_str, false)?;
+        }
+        
+        if let Some(e @ ('e' | 'E')) = self.current_char {
+            is_float = true;
+            let (line, column) = (self.line, self.column);
+            num_str.push(e);
+            self.advance();
+            
+            if let Some(sign @ ('+' | '-')) = self.current_char {
+                num_str.push(sign);
                 self.advance();
-            } else {
-                break;
+            }
+            if self.read_digits(10, &mut num_str, false)? == 0 {
+                return Err(KyaroError::lexer_error(
+                    format!("Invalid number literal '{}': exponent has no digits", num_str),
+                    line,
+                    column,
+                ));
             }
         }
         
-        let value = if has_dot {
-            num_str.parse::<f64>()
-                .map_err(|_| KyaroError::lexer_error("Invalid number format", start_line, start_column))?
+        let invalid = || KyaroError::lexer_error(format!("Invalid number literal '{}'", num_str), start_line, start_column);
+        if let Some('j' | 'J') = self.current_char {
+            self.advance();
+            num_str.parse::<f64>().map(TokenValue::Imaginary).map_err(|_| invalid())
+        } else if is_float {
+            num_str.parse::<f64>().map(TokenValue::Float).map_err(|_| invalid())
+        } else if num_str.starts_with('0') && num_str.contains(|c: char| c != '0') {
+            // As in Python, `08` is an error rather than eight; zero may still be written `00`
+            Err(KyaroError::lexer_error(
+                format!("Invalid number literal '{}': leading zeros are not allowed (use 0o for octal)", num_str),
+                start_line,
+                start_column,
+            ))
         } else {
-            num_str.parse::<i64>()
-                .map_err(|_| KyaroError::lexer_error("Invalid number format", start_line, start_column))?
-                as f64
-        };
+            num_str.parse::<BigInt>().map(TokenValue::Int).map_err(|_| invalid())
+        }
+    }
+    
+    fn read_radix_number(&mut self, radix: u32, name: &str) -> Result<TokenValue> {
+        let start_line = self.line;
+        let start_column = self.column;
+        self.advance(); // Skip '0'
+        let prefix = self.current_char.unwrap_or_default();
+        self.advance(); // Skip the base letter
+        
+        let mut digits = String::new();
+        let count = self.read_digits(radix, &mut digits, true)?;
+        
+        if let Some(ch) = self.current_char {
+            if ch.is_ascii_alphanumeric() {
+                return Err(KyaroError::lexer_error(
+                    format!("Invalid digit '{}' in {} literal", ch, name),
+                    self.line,
+                    self.column,
+                ));
+            }
+        }
+        if count == 0 {
+            return Err(KyaroError::lexer_error(
+                format!("Invalid {} literal: expected digits after '0{}'", name, prefix),
+                start_line,
+                start_column,
+            ));
+        }
+        
+        BigInt::parse_bytes(digits.as_bytes(), radix).map(TokenValue::Int).ok_or_else(|| {
+            KyaroError::lexer_error(format!("Invalid {} literal", name), start_line, start_column)
+        })
+    }
+    
+    /// Reads digits of `radix` into `text`, skipping the underscores between
+    /// them, and returns how many digits there were. An underscore may come
+    /// first only when `leading_underscore` is set (right after a base prefix).
+    fn read_digits(&mut self, radix: u32, text: &mut String, leading_underscore: bool) -> Result<usize> {
+        let mut count = 0;
+        
+        loop {
+            match self.current_char {
+                Some(ch) if ch.is_digit(radix) => {
+                    text.push(ch);
+                    self.advance();
+                    count += 1;
+                }
+                Some('_') => {
+                    let (line, column) = (self.line, self.column);
+                    self.advance();
+                    let followed_by_digit = self.current_char.is_some_and(|ch| ch.is_digit(radix));
+                    if !followed_by_digit || (count == 0 && !leading_underscore) {
+                        return Err(KyaroError::lexer_error(
+                            "Invalid number literal: '_' must separate digits",
+                            line,
+                            column,
+                        ));
+                    }
+                }
+                _ => break,
+            }
+        }
+        
+        Ok(count)
+    }
+    
+    /// Whether a string prefix (`f`, `r`, `rf` or `fr`) starts here.
+    fn at_string_prefix(&self) -> bool {
+        let is_prefix = |c: Option<char>| matches!(c, Some('f' | 'F' | 'r' | 'R'));
+        let is_quote = |c: Option<char>| matches!(c, Some('"' | '\''));
         
-        Ok(Token::number(value, start_line, start_column))
+        if !is_prefix(self.peek(0)) {
+            return false;
+        }
+        if is_quote(self.peek(1)) {
+            return true;
+        }
+        let differs = self.peek(0).map(|c| c.to_ascii_lowercase()) != self.peek(1).map(|c| c.to_ascii_lowercase());
+        is_prefix(self.peek(1)) && differs && is_quote(self.peek(2))
     }
     
-    fn read_string(&mut self, quote_char: char) -> Result<Token> {
+    /// Reads a string literal with its optional prefix. Raw strings keep
+    /// backslashes as written; f-strings split into text and `{expression}`
+    /// segments. Tripled quotes delimit a string that may span lines.
+    fn read_string(&mut self) -> Result<Token> {
         let start_line = self.line;
         let start_column = self.column;
-        self.advance(); // Skip opening quote
+        
+        let (mut raw, mut format) = (false, false);
+        while let Some(ch) = self.current_char {
+            match ch.to_ascii_lowercase() {
+                'r' => raw = true,
+                'f' => format = true,
+                _ => break,
+            }
+            self.advance();
+        }
+        
+        let Some(quote_char) = self.current_char else {
+            return Err(KyaroError::lexer_error("Unterminated string", start_line, start_column));
+        };
+        let triple = self.peek(1) == Some(quote_char) && self.peek(2) == Some(quote_char);
+        let quote_len = if triple { 3 } else { 1 };
+        for _ in 0..quote_len {
+            self.advance(); // Skip opening quote
+        }
         
         let mut string_value = String::new();
+        let mut segments = Vec::new();
         
-        while let Some(ch) = self.current_char {
-            if ch == quote_char {
+        loop {
+            let Some(ch) = self.current_char else {
+                return Err(KyaroError::lexer_error("Unterminated string", start_line, start_column));
+            };
+            
+            if ch == quote_char && (!triple || (self.peek(1) == Some(quote_char) && self.peek(2) == Some(quote_char))) {
                 break;
             }
             
             if ch == '\\' {
                 self.advance();
-                match self.current_char {
-                    Some('n') => string_value.push('\n'),
-                    Some('t') => string_value.push('\t'),
-                    Some('r') => string_value.push('\r'),
-                    Some('\\') => string_value.push('\\'),
-                    Some(c) if c == quote_char => string_value.push(quote_char),
-                    Some(c) => string_value.push(c),
-                    None => return Err(KyaroError::lexer_error("Unterminated string", start_line, start_column)),
+                let Some(escaped) = self.current_char else {
+                    return Err(KyaroError::lexer_error("Unterminated string", start_line, start_column));
+                };
+                if raw {
+                    string_value.push('\\');
+                    string_value.push(escaped);
+                } else {
+                    string_value.push(match escaped {
+                        'n' => '\n',
+                        't' => '\t',
+                        'r' => '\r',
+                        other => other,
+                    });
+                }
+                self.advance();
+            } else if format && ch == '{' {
+                self.advance();
+                if self.current_char == Some('{') {
+                    string_value.push('{');
+                    self.advance();
+                    continue;
+                }
+                
+                if !string_value.is_empty() {
+                    segments.push(FStringSegment::Text(std::mem::take(&mut string_value)));
+                }
+                segments.push(self.read_interpolation()?);
+            } else if format && ch == '}' {
+                let (line, column) = (self.line, self.column);
+                self.advance();
+                if self.current_char != Some('}') {
+                    return Err(KyaroError::lexer_error("Single '}' is not allowed in f-string", line, column));
                 }
+                string_value.push('}');
                 self.advance();
             } else {
                 string_value.push(ch);
@@ -133,12 +329,78 @@ impl Lexer {
             }
         }
         
-        if self.current_char != Some(quote_char) {
-            return Err(KyaroError::lexer_error("Unterminated string", start_line, start_column));
+        for _ in 0..quote_len {
+            self.advance(); // Skip closing quote
+        }
+        
+        if !format {
+            return Ok(Token::string(string_value, start_line, start_column));
+        }
+        if !string_value.is_empty() {
+            segments.push(FStringSegment::Text(string_value));
+        }
+        Ok(Token::new(TokenType::FString, TokenValue::FString(segments), start_line, start_column))
+    }
+    
+    /// Reads an f-string's `{expression[:spec]}` after the opening brace and
+    /// tokenizes the expression where it stands in the source.
+    fn read_interpolation(&mut self) -> Result<FStringSegment> {
+        let (line, column) = (self.line, self.column);
+        let unterminated = || KyaroError::lexer_error("Unterminated expression in f-string", line, column);
+        
+        let mut source = String::new();
+        let mut depth = 0usize;
+        let mut spec = None;
+        
+        loop {
+            let ch = self.current_char.ok_or_else(unterminated)?;
+            match ch {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' => depth = depth.saturating_sub(1),
+                '}' if depth > 0 => depth -= 1,
+                '}' => {
+                    self.advance();
+                    break;
+                }
+                ':' if depth == 0 => {
+                    self.advance();
+                    let mut format_spec = String::new();
+                    while self.current_char != Some('}') {
+                        format_spec.push(self.current_char.ok_or_else(unterminated)?);
+                        self.advance();
+                    }
+                    self.advance();
+                    spec = Some(format_spec);
+                    break;
+                }
+                '"' | '\'' => {
+                    // Copy a nested string literal whole, so its braces and colons are not special
+                    source.push(ch);
+                    self.advance();
+                    while let Some(inner) = self.current_char {
+                        source.push(inner);
+                        self.advance();
+                        if inner == '\\' {
+                            source.push(self.current_char.ok_or_else(unterminated)?);
+                            self.advance();
+                        } else if inner == ch {
+                            break;
+                        }
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            source.push(ch);
+            self.advance();
+        }
+        
+        if source.trim().is_empty() {
+            return Err(KyaroError::lexer_error("Empty expression in f-string", line, column));
         }
         
-        self.advance(); // Skip closing quote
-        Ok(Token::string(string_value, start_line, start_column))
+        let tokens = Lexer::embedded(&source, line, column).tokenize()?;
+        Ok(FStringSegment::Expression { tokens, spec })
     }
     
     fn read_identifier(&mut self) -> Token {
@@ -189,8 +451,8 @@ impl Lexer {
                     continue;
                 }
                 
-                if ch == '"' || ch == '\'' {
-                    tokens.push(self.read_string(ch)?);
+                if ch == '"' || ch == '\'' || self.at_string_prefix() {
+                    tokens.push(self.read_string()?);
                     continue;
                 }
                 
@@ -231,7 +493,12 @@ impl Lexer {
                             tokens.push(Token::symbol(TokenType::StarEquals, "*=".to_string(), start_line, start_column));
                         } else if self.current_char == Some('*') {
                             self.advance();
-                            tokens.push(Token::symbol(TokenType::Power, "**".to_string(), start_line, start_column));
+                            if self.current_char == Some('=') {
+                                self.advance();
+                                tokens.push(Token::symbol(TokenType::PowerEquals, "**=".to_string(), start_line, start_column));
+                            } else {
+                                tokens.push(Token::symbol(TokenType::Power, "**".to_string(), start_line, start_column));
+                            }
                         } else {
                             tokens.push(Token::symbol(TokenType::Star, "*".to_string(), start_line, start_column));
                         }
@@ -241,13 +508,26 @@ impl Lexer {
                         if self.current_char == Some('=') {
                             self.advance();
                             tokens.push(Token::symbol(TokenType::SlashEquals, "/=".to_string(), start_line, start_column));
+                        } else if self.current_char == Some('/') {
+                            self.advance();
+                            if self.current_char == Some('=') {
+                                self.advance();
+                                tokens.push(Token::symbol(TokenType::SlashSlashEquals, "//=".to_string(), start_line, start_column));
+                            } else {
+                                tokens.push(Token::symbol(TokenType::SlashSlash, "//".to_string(), start_line, start_column));
+                            }
                         } else {
                             tokens.push(Token::symbol(TokenType::Slash, "/".to_string(), start_line, start_column));
                         }
                     }
                     '%' => {
                         self.advance();
-                        tokens.push(Token::symbol(TokenType::Percent, "%".to_string(), start_line, start_column));
+                        if self.current_char == Some('=') {
+                            self.advance();
+                            tokens.push(Token::symbol(TokenType::PercentEquals, "%=".to_string(), start_line, start_column));
+                        } else {
+                            tokens.push(Token::symbol(TokenType::Percent, "%".to_string(), start_line, start_column));
+                        }
                     }
                     '=' => {
                         self.advance();
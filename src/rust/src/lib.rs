@@ -7,11 +7,15 @@ pub mod interpreter;
 pub mod environment;
 pub mod builtins;
 pub mod ai_ml_functions;
+pub mod file_system_functions;
+pub mod image_functions;
+pub mod native_modules;
 
-pub use errors::{KyaroError, Result};
+pub use errors::{Frame, KyaroError, Result};
 pub use token_types::{Token, TokenType, TokenValue};
 pub use lexer::Lexer;
 pub use ast_nodes::ASTNode;
 pub use parser::Parser;
 pub use interpreter::Interpreter;
-pub use environment::{Environment, Value};
+pub use environment::{DictKey, DictRef, EnvRef, Environment, ListRef, Module, Value};
+pub use builtins::{NativeContext, NativeFn};
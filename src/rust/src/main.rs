@@ -3,7 +3,11 @@ use std::fs;
 use std::io::{self, Write};
 use std::process;
 
-use laminax_kpl::{Lexer, Parser, Interpreter, KyaroError};
+use laminax_kpl::{ASTNode, Lexer, Parser, Interpreter, KyaroError};
+
+/// Each Kyaro call recurses through the evaluator, so programs run on a
+/// thread with room for `MAX_CALL_DEPTH` nested calls, even in debug builds.
+const STACK_SIZE: usize = 512 * 1024 * 1024;
 
 fn run_repl() {
     println!("Laminax Kyaro Programming Language v1.0 (Rust)");
@@ -11,6 +15,7 @@ fn run_repl() {
     println!("Type 'exit()' to quit\n");
     
     let mut interpreter = Interpreter::new();
+    let mut inputs = 0;
     
     loop {
         print!("kyaro> ");
@@ -30,13 +35,15 @@ fn run_repl() {
                     break;
                 }
                 
-                match execute_code(line, &mut interpreter) {
+                inputs += 1;
+                let name = format!("<input-{}>", inputs);
+                match parse(line).and_then(|ast| interpreter.interpret_input(ast, &name)) {
                     Ok(Some(result)) => {
-                        println!("{}", result.to_string());
+                        println!("{}", result);
                     }
                     Ok(None) => {}
                     Err(e) => {
-                        eprintln!("{}", e);
+                        eprintln!("{}", e.report(line, &name));
                     }
                 }
             }
@@ -52,8 +59,9 @@ fn run_file(filename: &str) {
     match fs::read_to_string(filename) {
         Ok(source) => {
             let mut interpreter = Interpreter::new();
-            if let Err(e) = execute_code(&source, &mut interpreter) {
-                eprintln!("{}", e);
+            interpreter.set_script_path(filename);
+            if let Err(e) = parse(&source).and_then(|ast| interpreter.interpret(ast)) {
+                eprintln!("{}", e.report(&source, filename));
                 process::exit(1);
             }
         }
@@ -64,25 +72,27 @@ fn run_file(filename: &str) {
     }
 }
 
-fn execute_code(source: &str, interpreter: &mut Interpreter) -> Result<Option<laminax_kpl::Value>, KyaroError> {
+fn parse(source: &str) -> Result<ASTNode, KyaroError> {
     let mut lexer = Lexer::new(source);
     let tokens = lexer.tokenize()?;
     
     let mut parser = Parser::new(tokens);
-    let ast = parser.parse()?;
-    
-    interpreter.interpret(ast)
+    parser.parse()
 }
 
 fn main() {
     let args: Vec<String> = env::args().collect();
     
-    match args.len() {
+    let interpreter = std::thread::Builder::new().stack_size(STACK_SIZE).spawn(move || match args.len() {
         1 => run_repl(),
         2 => run_file(&args[1]),
         _ => {
             eprintln!("Usage: {} [filename.kyaro]", args[0]);
             process::exit(1);
         }
+    });
+    
+    if interpreter.and_then(|thread| thread.join().map_err(|_| io::Error::other("interpreter panicked"))).is_err() {
+        process::exit(101);
     }
 }
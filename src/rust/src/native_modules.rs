@@ -0,0 +1,78 @@
+use crate::ai_ml_functions::{get_ai_ml_context_functions, get_ai_ml_functions};
+use crate::builtins::{get_builtin_functions, NativeFn};
+use crate::environment::{Environment, Value};
+use crate::file_system_functions::get_file_system_functions;
+use crate::image_functions::get_image_functions;
+use std::collections::HashMap;
+
+const MATH_FUNCTIONS: &[&str] = &[
+    "abs", "min", "max", "sum", "sqrt", "pow", "exp", "log", "log10", "log2", "ln",
+    "floor", "ceil", "round", "trunc", "sin", "cos", "tan", "asin", "acos", "atan", "atan2",
+    "sinh", "cosh", "tanh", "asinh", "acosh", "atanh", "degrees", "radians", "hypot",
+    "factorial", "gcd", "complex", "real", "imag", "conj", "phase",
+];
+
+const RANDOM_FUNCTIONS: &[&str] = &["random", "randint", "uniform", "choice"];
+
+const STATS_FUNCTIONS: &[&str] = &["mean", "median", "stdev", "variance"];
+
+/// Builds the top-level scope of a native module for `import name`.
+/// Returns `None` when no native module has that name.
+pub fn get_native_module(name: &str) -> Option<Environment> {
+    let mut scope = Environment::new();
+    
+    match name {
+        "math" => {
+            export_named(&mut scope, &get_builtin_functions(), MATH_FUNCTIONS);
+            scope.define("pi".to_string(), Value::Float(std::f64::consts::PI));
+            scope.define("e".to_string(), Value::Float(std::f64::consts::E));
+        }
+        "random" => export_named(&mut scope, &get_builtin_functions(), RANDOM_FUNCTIONS),
+        "stats" => export_named(&mut scope, &get_builtin_functions(), STATS_FUNCTIONS),
+        "ml" => {
+            export_prefixed(&mut scope, &get_ai_ml_functions(), "ml_", true);
+            export_prefixed(&mut scope, &get_ai_ml_context_functions(), "ml_", true);
+        }
+        "nn" => {
+            let functions = get_ai_ml_functions();
+            export_prefixed(&mut scope, &functions, "nn_", true);
+            export_prefixed(&mut scope, &functions, "matrix_", false);
+            export_named(&mut scope, &functions, &["gradient_descent_step", "adam_step"]);
+        }
+        "fs" => {
+            let functions = get_file_system_functions();
+            export_prefixed(&mut scope, &functions, "fs_", true);
+            export_prefixed(&mut scope, &functions, "path_", false);
+        }
+        "image" => export_prefixed(&mut scope, &get_image_functions(), "image_", true),
+        _ => return None,
+    }
+    
+    Some(scope)
+}
+
+fn export_named<F: Copy + Into<NativeFn>>(scope: &mut Environment, functions: &HashMap<String, F>, names: &[&str]) {
+    for name in names {
+        if let Some(function) = functions.get(*name) {
+            let name = name.to_string();
+            scope.define(name.clone(), Value::NativeFunction { name, function: (*function).into() });
+        }
+    }
+}
+
+/// Exports every function whose flat name starts with `prefix`, dropping the
+/// prefix from the module member name when `strip` is set (`ml_mse` -> `ml.mse`).
+fn export_prefixed<F: Copy + Into<NativeFn>>(
+    scope: &mut Environment,
+    functions: &HashMap<String, F>,
+    prefix: &str,
+    strip: bool,
+) {
+    for (name, function) in functions {
+        if let Some(short_name) = name.strip_prefix(prefix) {
+            let member = if strip { short_name } else { name.as_str() };
+            let function = (*function).into();
+            scope.define(member.to_string(), Value::NativeFunction { name: name.clone(), function });
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use crate::errors::{KyaroError, Result};
-use crate::token_types::{Token, TokenType, TokenValue};
+use crate::token_types::{FStringSegment, Token, TokenType, TokenValue};
 use crate::ast_nodes::*;
 
 pub struct Parser {
@@ -10,7 +10,7 @@ pub struct Parser {
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        let current_token = tokens.get(0).cloned();
+        let current_token = tokens.first().cloned();
         Self {
             tokens,
             position: 0,
@@ -90,18 +90,27 @@ impl Parser {
         if let Some(ref token) = self.current_token {
             match token.token_type {
                 TokenType::KeywordLet => self.parse_let_statement(),
-                TokenType::KeywordFunc => self.parse_function(),
+                // `func(` starts an anonymous function expression
+                TokenType::KeywordFunc if !matches!(self.peek(1), Some(next) if next.token_type == TokenType::LParen) => {
+                    self.parse_function()
+                }
+                TokenType::KeywordClass => self.parse_class(),
+                TokenType::KeywordImport => self.parse_import(),
                 TokenType::KeywordIf => self.parse_if_statement(),
                 TokenType::KeywordWhile => self.parse_while_statement(),
                 TokenType::KeywordFor => self.parse_for_statement(),
                 TokenType::KeywordReturn => self.parse_return_statement(),
+                TokenType::KeywordTry => self.parse_try_statement(),
+                TokenType::KeywordThrow => self.parse_throw_statement(),
                 TokenType::KeywordBreak => {
+                    let span = self.current_span();
                     self.advance();
-                    Ok(ASTNode::Break(BreakNode::new()))
+                    Ok(ASTNode::Break(BreakNode::new(span)))
                 }
                 TokenType::KeywordContinue => {
+                    let span = self.current_span();
                     self.advance();
-                    Ok(ASTNode::Continue(ContinueNode::new()))
+                    Ok(ASTNode::Continue(ContinueNode::new(span)))
                 }
                 _ => self.parse_expression_statement(),
             }
@@ -125,6 +134,10 @@ impl Parser {
     }
     
     fn parse_function(&mut self) -> Result<ASTNode> {
+        Ok(ASTNode::Function(self.parse_function_definition()?))
+    }
+    
+    fn parse_function_definition(&mut self) -> Result<FunctionNode> {
         self.advance(); // consume 'func'
         let name_token = self.expect(TokenType::Identifier)?;
         let name = match name_token.value {
@@ -132,20 +145,69 @@ impl Parser {
             _ => return Err(KyaroError::parser_error("Expected identifier", name_token.line, name_token.column)),
         };
         
+        let parameters = self.parse_parameters()?;
+        let body = self.parse_block()?;
+        
+        Ok(FunctionNode::new(name, parameters, body))
+    }
+    
+    /// Parses a parenthesized parameter list: plain and defaulted
+    /// parameters, then optionally `*rest`, keyword-only parameters and `**keywords`.
+    fn parse_parameters(&mut self) -> Result<ParameterList> {
         self.expect(TokenType::LParen)?;
         
-        let mut parameters = Vec::new();
+        let mut list = ParameterList::default();
+        let mut names = Vec::new();
         while let Some(ref token) = self.current_token {
             if token.token_type == TokenType::RParen {
                 break;
             }
             
-            let param_token = self.expect(TokenType::Identifier)?;
-            let param_name = match param_token.value {
-                TokenValue::Identifier(name) => name,
-                _ => return Err(KyaroError::parser_error("Expected identifier", param_token.line, param_token.column)),
+            let span = self.current_span();
+            if list.keywords.is_some() {
+                return Err(KyaroError::parser_error("No parameters may follow '**' parameter", span.line, span.column));
+            }
+            
+            let name = if self.check(TokenType::Power) {
+                self.advance();
+                let name = self.parse_identifier()?;
+                list.keywords = Some(name.clone());
+                name
+            } else if self.check(TokenType::Star) {
+                if list.rest.is_some() {
+                    return Err(KyaroError::parser_error("Only one '*' parameter is allowed", span.line, span.column));
+                }
+                self.advance();
+                let name = self.parse_identifier()?;
+                list.rest = Some(name.clone());
+                list.positional = list.parameters.len();
+                name
+            } else {
+                let name = self.parse_identifier()?;
+                let default = if self.check(TokenType::Equals) {
+                    self.advance();
+                    Some(self.parse_expression()?)
+                } else {
+                    None
+                };
+                
+                // Keyword-only parameters may be required wherever they appear
+                let after_default = list.parameters.last().is_some_and(|p| p.default.is_some());
+                if default.is_none() && after_default && list.rest.is_none() {
+                    return Err(KyaroError::parser_error(
+                        format!("Parameter '{}' without a default follows a parameter with one", name),
+                        span.line,
+                        span.column,
+                    ));
+                }
+                list.parameters.push(Parameter { name: name.clone(), default });
+                name
             };
-            parameters.push(param_name);
+            
+            if names.contains(&name) {
+                return Err(KyaroError::parser_error(format!("Duplicate parameter '{}'", name), span.line, span.column));
+            }
+            names.push(name);
             
             if let Some(ref token) = self.current_token {
                 if token.token_type == TokenType::Comma {
@@ -155,56 +217,232 @@ impl Parser {
         }
         
         self.expect(TokenType::RParen)?;
+        if list.rest.is_none() {
+            list.positional = list.parameters.len();
+        }
+        Ok(list)
+    }
+    
+    /// Parses the expressions embedded in an f-string. Each was tokenized on
+    /// its own and must be exactly one expression.
+    fn parse_fstring(&mut self, segments: Vec<FStringSegment>) -> Result<ASTNode> {
+        let mut parts = Vec::with_capacity(segments.len());
+        for segment in segments {
+            match segment {
+                FStringSegment::Text(text) => parts.push(FStringPart::Text(text)),
+                FStringSegment::Expression { tokens, spec } => {
+                    let mut parser = Parser::new(tokens);
+                    parser.skip_newlines();
+                    let span = parser.current_span();
+                    let value = parser.parse_expression()?;
+                    parser.skip_newlines();
+                    
+                    if let Some(ref token) = parser.current_token {
+                        if token.token_type != TokenType::Eof {
+                            return Err(KyaroError::parser_error(
+                                format!("Unexpected token in f-string expression: {:?}", token.token_type),
+                                token.line,
+                                token.column,
+                            ));
+                        }
+                    }
+                    parts.push(FStringPart::Expression { value, spec, span });
+                }
+            }
+        }
+        
+        Ok(ASTNode::FString(FStringNode::new(parts)))
+    }
+    
+    fn parse_identifier(&mut self) -> Result<String> {
+        let token = self.expect(TokenType::Identifier)?;
+        match token.value {
+            TokenValue::Identifier(name) => Ok(name),
+            _ => Err(KyaroError::parser_error("Expected identifier", token.line, token.column)),
+        }
+    }
+    
+    /// `func(a, b) { ... }` used as an expression.
+    fn parse_anonymous_function(&mut self) -> Result<ASTNode> {
+        self.advance(); // consume 'func'
+        let parameters = self.parse_parameters()?;
+        let body = self.parse_block()?;
+        
+        Ok(ASTNode::Lambda(FunctionNode::new("<lambda>".to_string(), parameters, body)))
+    }
+    
+    /// `(a, b) -> expr` or `x -> expr`; the function returns the expression.
+    fn parse_arrow_function(&mut self) -> Result<ASTNode> {
+        let parameters = if self.check(TokenType::LParen) {
+            self.parse_parameters()?
+        } else {
+            let name = self.parse_identifier()?;
+            ParameterList {
+                parameters: vec![Parameter { name, default: None }],
+                positional: 1,
+                ..ParameterList::default()
+            }
+        };
+        
+        self.expect(TokenType::Arrow)?;
+        let span = self.current_span();
+        let value = self.parse_expression()?;
+        let body = ASTNode::Block(BlockNode::new(vec![ASTNode::Return(ReturnNode::new(Some(value), span))]));
+        
+        Ok(ASTNode::Lambda(FunctionNode::new("<lambda>".to_string(), parameters, body)))
+    }
+    
+    /// Whether the tokens ahead are an arrow function's parameters
+    /// followed by `->`, as opposed to a parenthesized expression.
+    fn at_arrow_function(&self) -> bool {
+        let token_type = |offset: usize| self.peek(offset).map(|token| &token.token_type);
+        
+        if token_type(0) == Some(&TokenType::Identifier) {
+            return token_type(1) == Some(&TokenType::Arrow);
+        }
+        if token_type(0) != Some(&TokenType::LParen) {
+            return false;
+        }
+        
+        // Find the matching `)`; parameter defaults may contain parentheses
+        let mut depth = 0;
+        let mut offset = 0;
+        loop {
+            match token_type(offset) {
+                Some(TokenType::LParen) => depth += 1,
+                Some(TokenType::RParen) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                Some(TokenType::Eof) | None => return false,
+                _ => {}
+            }
+            offset += 1;
+        }
+        token_type(offset + 1) == Some(&TokenType::Arrow)
+    }
+    
+    fn parse_class(&mut self) -> Result<ASTNode> {
+        let span = self.current_span();
+        self.advance(); // consume 'class'
+        let name_token = self.expect(TokenType::Identifier)?;
+        let name = match name_token.value {
+            TokenValue::Identifier(name) => name,
+            _ => return Err(KyaroError::parser_error("Expected identifier", name_token.line, name_token.column)),
+        };
+        
+        let superclass = if self.check(TokenType::LParen) {
+            self.advance();
+            let superclass = self.parse_expression()?;
+            self.expect(TokenType::RParen)?;
+            Some(superclass)
+        } else {
+            None
+        };
+        
         self.expect(TokenType::LBrace)?;
         self.skip_newlines();
         
-        let mut body = Vec::new();
+        let mut methods = Vec::new();
         while let Some(ref token) = self.current_token {
-            if token.token_type == TokenType::RBrace {
-                break;
+            match token.token_type {
+                TokenType::RBrace => break,
+                TokenType::KeywordFunc => methods.push(self.parse_function_definition()?),
+                _ => {
+                    return Err(KyaroError::parser_error(
+                        "Expected method definition in class body",
+                        token.line,
+                        token.column,
+                    ))
+                }
             }
-            
-            let stmt = self.parse_statement()?;
-            body.push(stmt);
             self.skip_newlines();
         }
         
         self.expect(TokenType::RBrace)?;
+        Ok(ASTNode::Class(ClassNode::new(name, superclass, methods, span)))
+    }
+    
+    fn parse_import(&mut self) -> Result<ASTNode> {
+        let span = self.current_span();
+        self.advance(); // consume 'import'
+        
+        let (module, is_path) = match self.current_token {
+            Some(Token { value: TokenValue::String(ref path), .. }) => (path.clone(), true),
+            Some(Token { token_type: TokenType::Identifier, value: TokenValue::Identifier(ref name), .. }) => {
+                (name.clone(), false)
+            }
+            Some(ref token) => {
+                return Err(KyaroError::parser_error(
+                    "Expected module name or path after 'import'",
+                    token.line,
+                    token.column,
+                ))
+            }
+            None => return Err(KyaroError::parser_error("Unexpected end of input", 0, 0)),
+        };
+        self.advance();
         
-        Ok(ASTNode::Function(FunctionNode::new(
-            name,
-            parameters,
-            ASTNode::Block(BlockNode::new(body)),
-        )))
+        Ok(ASTNode::Import(ImportNode::new(module, is_path, span)))
     }
     
-    fn parse_if_statement(&mut self) -> Result<ASTNode> {
-        self.advance(); // consume 'if'
-        let condition = self.parse_expression()?;
+    fn parse_block(&mut self) -> Result<ASTNode> {
         self.expect(TokenType::LBrace)?;
         self.skip_newlines();
         
-        let mut then_branch = Vec::new();
+        let mut statements = Vec::new();
         while let Some(ref token) = self.current_token {
             if token.token_type == TokenType::RBrace {
                 break;
             }
             
             let stmt = self.parse_statement()?;
-            then_branch.push(stmt);
+            statements.push(stmt);
             self.skip_newlines();
         }
         
         self.expect(TokenType::RBrace)?;
+        Ok(ASTNode::Block(BlockNode::new(statements)))
+    }
+    
+    fn current_span(&self) -> Span {
+        match self.current_token {
+            Some(ref token) => Span::new(token.line, token.column),
+            None => Span::default(),
+        }
+    }
+    
+    fn check(&self, token_type: TokenType) -> bool {
+        matches!(self.current_token, Some(ref token) if token.token_type == token_type)
+    }
+    
+    fn parse_if_statement(&mut self) -> Result<ASTNode> {
+        self.advance(); // consume 'if'
+        let condition = self.parse_expression()?;
+        let then_branch = self.parse_block()?;
         self.skip_newlines();
         
-        // For now, simplified - no elif/else support
-        let elif_branches = Vec::new();
-        let else_branch = None;
+        let mut elif_branches = Vec::new();
+        while self.check(TokenType::KeywordElif) {
+            self.advance(); // consume 'elif'
+            let elif_condition = self.parse_expression()?;
+            let elif_body = self.parse_block()?;
+            self.skip_newlines();
+            elif_branches.push((elif_condition, elif_body));
+        }
+        
+        let else_branch = if self.check(TokenType::KeywordElse) {
+            self.advance(); // consume 'else'
+            Some(self.parse_block()?)
+        } else {
+            None
+        };
         
         Ok(ASTNode::If(IfNode::new(
             condition,
-            ASTNode::Block(BlockNode::new(then_branch)),
+            then_branch,
             elif_branches,
             else_branch,
         )))
@@ -236,6 +474,7 @@ impl Parser {
     }
     
     fn parse_for_statement(&mut self) -> Result<ASTNode> {
+        let span = self.current_span();
         self.advance(); // consume 'for'
         let var_token = self.expect(TokenType::Identifier)?;
         let variable = match var_token.value {
@@ -265,25 +504,103 @@ impl Parser {
             variable,
             iterable,
             ASTNode::Block(BlockNode::new(body)),
+            span,
         )))
     }
     
+    fn parse_try_statement(&mut self) -> Result<ASTNode> {
+        let span = self.current_span();
+        self.advance(); // consume 'try'
+        let body = self.parse_block()?;
+        self.skip_newlines();
+        
+        let mut catch_variable = None;
+        let mut catch_body = None;
+        if self.check(TokenType::KeywordCatch) {
+            self.advance(); // consume 'catch'
+            if let Some(Token { token_type: TokenType::Identifier, value: TokenValue::Identifier(ref name), .. }) =
+                self.current_token
+            {
+                catch_variable = Some(name.clone());
+                self.advance();
+            }
+            catch_body = Some(self.parse_block()?);
+            self.skip_newlines();
+        }
+        
+        let finally_body = if self.check(TokenType::KeywordFinally) {
+            self.advance(); // consume 'finally'
+            Some(self.parse_block()?)
+        } else {
+            None
+        };
+        
+        if catch_body.is_none() && finally_body.is_none() {
+            return Err(KyaroError::parser_error(
+                "Expected 'catch' or 'finally' after try block",
+                span.line,
+                span.column,
+            ));
+        }
+        
+        Ok(ASTNode::Try(TryNode::new(body, catch_variable, catch_body, finally_body)))
+    }
+    
+    fn parse_throw_statement(&mut self) -> Result<ASTNode> {
+        let span = self.current_span();
+        self.advance(); // consume 'throw'
+        let value = self.parse_expression()?;
+        Ok(ASTNode::Throw(ThrowNode::new(value, span)))
+    }
+    
     fn parse_return_statement(&mut self) -> Result<ASTNode> {
+        let span = self.current_span();
         self.advance(); // consume 'return'
         
         // Check if there's an expression to return
         if let Some(ref token) = self.current_token {
-            if token.token_type == TokenType::Newline || token.token_type == TokenType::RBrace {
-                return Ok(ASTNode::Return(ReturnNode::new(None)));
+            if matches!(token.token_type, TokenType::Newline | TokenType::RBrace | TokenType::Eof) {
+                return Ok(ASTNode::Return(ReturnNode::new(None, span)));
             }
         }
         
         let value = self.parse_expression()?;
-        Ok(ASTNode::Return(ReturnNode::new(Some(value))))
+        Ok(ASTNode::Return(ReturnNode::new(Some(value), span)))
     }
     
     fn parse_expression_statement(&mut self) -> Result<ASTNode> {
-        self.parse_expression()
+        let expr = self.parse_expression()?;
+        
+        let token = match self.current_token {
+            Some(ref token) => token.clone(),
+            None => return Ok(expr),
+        };
+        
+        let span = Span::new(token.line, token.column);
+        match token.token_type {
+            TokenType::Equals
+            | TokenType::PlusEquals
+            | TokenType::MinusEquals
+            | TokenType::StarEquals
+            | TokenType::SlashEquals
+            | TokenType::SlashSlashEquals
+            | TokenType::PercentEquals
+            | TokenType::PowerEquals => {
+                if !matches!(expr, ASTNode::Identifier(_) | ASTNode::Index(_) | ASTNode::MemberAccess(_)) {
+                    return Err(KyaroError::parser_error("Invalid assignment target", token.line, token.column));
+                }
+                
+                self.advance();
+                let value = self.parse_expression()?;
+                
+                if token.token_type == TokenType::Equals {
+                    Ok(ASTNode::Reassignment(ReassignmentNode::new(expr, value, span)))
+                } else {
+                    Ok(ASTNode::CompoundAssignment(CompoundAssignmentNode::new(expr, token.token_type, value, span)))
+                }
+            }
+            _ => Ok(expr),
+        }
     }
     
     fn parse_expression(&mut self) -> Result<ASTNode> {
@@ -296,9 +613,10 @@ impl Parser {
         while let Some(ref token) = self.current_token {
             if token.token_type == TokenType::KeywordOr {
                 let operator = token.token_type.clone();
+                let span = Span::new(token.line, token.column);
                 self.advance();
                 let right = self.parse_logical_and()?;
-                left = ASTNode::BinaryOp(BinaryOpNode::new(left, operator, right));
+                left = ASTNode::BinaryOp(BinaryOpNode::new(left, operator, right, span));
             } else {
                 break;
             }
@@ -313,9 +631,10 @@ impl Parser {
         while let Some(ref token) = self.current_token {
             if token.token_type == TokenType::KeywordAnd {
                 let operator = token.token_type.clone();
+                let span = Span::new(token.line, token.column);
                 self.advance();
                 let right = self.parse_equality()?;
-                left = ASTNode::BinaryOp(BinaryOpNode::new(left, operator, right));
+                left = ASTNode::BinaryOp(BinaryOpNode::new(left, operator, right, span));
             } else {
                 break;
             }
@@ -331,9 +650,10 @@ impl Parser {
             match token.token_type {
                 TokenType::EqualsEquals | TokenType::NotEquals => {
                     let operator = token.token_type.clone();
+                    let span = Span::new(token.line, token.column);
                     self.advance();
                     let right = self.parse_comparison()?;
-                    left = ASTNode::BinaryOp(BinaryOpNode::new(left, operator, right));
+                    left = ASTNode::BinaryOp(BinaryOpNode::new(left, operator, right, span));
                 }
                 _ => break,
             }
@@ -350,9 +670,10 @@ impl Parser {
                 TokenType::LessThan | TokenType::GreaterThan | 
                 TokenType::LessEquals | TokenType::GreaterEquals => {
                     let operator = token.token_type.clone();
+                    let span = Span::new(token.line, token.column);
                     self.advance();
                     let right = self.parse_term()?;
-                    left = ASTNode::BinaryOp(BinaryOpNode::new(left, operator, right));
+                    left = ASTNode::BinaryOp(BinaryOpNode::new(left, operator, right, span));
                 }
                 _ => break,
             }
@@ -368,9 +689,10 @@ impl Parser {
             match token.token_type {
                 TokenType::Plus | TokenType::Minus => {
                     let operator = token.token_type.clone();
+                    let span = Span::new(token.line, token.column);
                     self.advance();
                     let right = self.parse_factor()?;
-                    left = ASTNode::BinaryOp(BinaryOpNode::new(left, operator, right));
+                    left = ASTNode::BinaryOp(BinaryOpNode::new(left, operator, right, span));
                 }
                 _ => break,
             }
@@ -384,11 +706,12 @@ impl Parser {
         
         while let Some(ref token) = self.current_token {
             match token.token_type {
-                TokenType::Star | TokenType::Slash | TokenType::Percent => {
+                TokenType::Star | TokenType::Slash | TokenType::SlashSlash | TokenType::Percent => {
                     let operator = token.token_type.clone();
+                    let span = Span::new(token.line, token.column);
                     self.advance();
                     let right = self.parse_unary()?;
-                    left = ASTNode::BinaryOp(BinaryOpNode::new(left, operator, right));
+                    left = ASTNode::BinaryOp(BinaryOpNode::new(left, operator, right, span));
                 }
                 _ => break,
             }
@@ -402,32 +725,73 @@ impl Parser {
             match token.token_type {
                 TokenType::Minus | TokenType::KeywordNot => {
                     let operator = token.token_type.clone();
+                    let span = Span::new(token.line, token.column);
                     self.advance();
                     let operand = self.parse_unary()?;
-                    return Ok(ASTNode::UnaryOp(UnaryOpNode::new(operator, operand)));
+                    return Ok(ASTNode::UnaryOp(UnaryOpNode::new(operator, operand, span)));
                 }
                 _ => {}
             }
         }
         
-        self.parse_call()
+        self.parse_power()
+    }
+    
+    /// `**` binds tighter than unary minus on its left and is right-associative,
+    /// so `-2 ** 2` is `-(2 ** 2)` and `2 ** 3 ** 2` is `2 ** (3 ** 2)`.
+    fn parse_power(&mut self) -> Result<ASTNode> {
+        let base = self.parse_call()?;
+        
+        if self.check(TokenType::Power) {
+            let span = self.current_span();
+            self.advance();
+            let exponent = self.parse_unary()?;
+            return Ok(ASTNode::BinaryOp(BinaryOpNode::new(base, TokenType::Power, exponent, span)));
+        }
+        
+        Ok(base)
     }
     
     fn parse_call(&mut self) -> Result<ASTNode> {
         let mut expr = self.parse_primary()?;
         
         while let Some(ref token) = self.current_token {
+            let span = Span::new(token.line, token.column);
             match token.token_type {
                 TokenType::LParen => {
                     self.advance();
                     let mut arguments = Vec::new();
+                    let mut keyword_arguments: Vec<(String, ASTNode)> = Vec::new();
                     
                     while let Some(ref token) = self.current_token {
                         if token.token_type == TokenType::RParen {
                             break;
                         }
                         
-                        arguments.push(self.parse_expression()?);
+                        // `name: value` passes an argument by keyword
+                        let argument_span = self.current_span();
+                        let is_keyword = token.token_type == TokenType::Identifier
+                            && matches!(self.peek(1), Some(next) if next.token_type == TokenType::Colon);
+                        if is_keyword {
+                            let name = self.parse_identifier()?;
+                            self.advance(); // consume ':'
+                            if keyword_arguments.iter().any(|(existing, _)| *existing == name) {
+                                return Err(KyaroError::parser_error(
+                                    format!("Keyword argument '{}' repeated", name),
+                                    argument_span.line,
+                                    argument_span.column,
+                                ));
+                            }
+                            keyword_arguments.push((name, self.parse_expression()?));
+                        } else if !keyword_arguments.is_empty() {
+                            return Err(KyaroError::parser_error(
+                                "Positional argument follows keyword argument",
+                                argument_span.line,
+                                argument_span.column,
+                            ));
+                        } else {
+                            arguments.push(self.parse_expression()?);
+                        }
                         
                         if let Some(ref token) = self.current_token {
                             if token.token_type == TokenType::Comma {
@@ -437,7 +801,20 @@ impl Parser {
                     }
                     
                     self.expect(TokenType::RParen)?;
-                    expr = ASTNode::Call(CallNode::new(expr, arguments));
+                    expr = ASTNode::Call(CallNode::new(expr, arguments, keyword_arguments, span));
+                }
+                TokenType::LBracket => {
+                    self.advance();
+                    expr = self.parse_subscript(expr, span)?;
+                }
+                TokenType::Dot => {
+                    self.advance();
+                    let member_token = self.expect(TokenType::Identifier)?;
+                    let member = match member_token.value {
+                        TokenValue::Identifier(name) => name,
+                        _ => return Err(KyaroError::parser_error("Expected identifier", member_token.line, member_token.column)),
+                    };
+                    expr = ASTNode::MemberAccess(MemberAccessNode::new(expr, member, span));
                 }
                 _ => break,
             }
@@ -446,18 +823,92 @@ impl Parser {
         Ok(expr)
     }
     
+    /// Parses the inside of `[...]` after the opening bracket: either a
+    /// plain index or a `start:end:step` slice with optional parts.
+    fn parse_subscript(&mut self, object: ASTNode, span: Span) -> Result<ASTNode> {
+        let start = if self.check(TokenType::Colon) {
+            None
+        } else {
+            let index = self.parse_expression()?;
+            if !self.check(TokenType::Colon) {
+                self.expect(TokenType::RBracket)?;
+                return Ok(ASTNode::Index(IndexNode::new(object, index, span)));
+            }
+            Some(index)
+        };
+        
+        self.advance(); // consume ':'
+        let end = if self.check(TokenType::Colon) || self.check(TokenType::RBracket) {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
+        
+        let step = if self.check(TokenType::Colon) {
+            self.advance(); // consume ':'
+            if self.check(TokenType::RBracket) {
+                None
+            } else {
+                Some(self.parse_expression()?)
+            }
+        } else {
+            None
+        };
+        
+        self.expect(TokenType::RBracket)?;
+        Ok(ASTNode::Slice(SliceNode::new(object, start, end, step, span)))
+    }
+    
+    /// Parses a `{key: value, ...}` literal, which may span several lines.
+    fn parse_dict(&mut self) -> Result<ASTNode> {
+        let span = self.current_span();
+        self.expect(TokenType::LBrace)?;
+        self.skip_newlines();
+        
+        let mut entries = Vec::new();
+        while !self.check(TokenType::RBrace) {
+            let key = self.parse_expression()?;
+            self.expect(TokenType::Colon)?;
+            let value = self.parse_expression()?;
+            entries.push((key, value));
+            self.skip_newlines();
+            
+            if !self.check(TokenType::Comma) {
+                break;
+            }
+            self.advance();
+            self.skip_newlines();
+        }
+        
+        self.expect(TokenType::RBrace)?;
+        Ok(ASTNode::Dict(DictNode::new(entries, span)))
+    }
+    
     fn parse_primary(&mut self) -> Result<ASTNode> {
+        if self.at_arrow_function() {
+            return self.parse_arrow_function();
+        }
+        
         if let Some(ref token) = self.current_token {
             match &token.token_type {
-                TokenType::Number => {
-                    if let TokenValue::Number(value) = &token.value {
+                TokenType::Number => match &token.value {
+                    TokenValue::Int(value) => {
+                        let value = value.clone();
+                        self.advance();
+                        Ok(ASTNode::Int(IntNode::new(value)))
+                    }
+                    TokenValue::Float(value) => {
                         let value = *value;
                         self.advance();
-                        Ok(ASTNode::Number(NumberNode::new(value)))
-                    } else {
-                        Err(KyaroError::parser_error("Invalid number token", token.line, token.column))
+                        Ok(ASTNode::Float(FloatNode::new(value)))
                     }
-                }
+                    TokenValue::Imaginary(value) => {
+                        let value = *value;
+                        self.advance();
+                        Ok(ASTNode::Imaginary(ImaginaryNode::new(value)))
+                    }
+                    _ => Err(KyaroError::parser_error("Invalid number token", token.line, token.column)),
+                },
                 TokenType::String => {
                     if let TokenValue::String(value) = &token.value {
                         let value = value.clone();
@@ -467,6 +918,15 @@ impl Parser {
                         Err(KyaroError::parser_error("Invalid string token", token.line, token.column))
                     }
                 }
+                TokenType::FString => {
+                    if let TokenValue::FString(segments) = &token.value {
+                        let segments = segments.clone();
+                        self.advance();
+                        self.parse_fstring(segments)
+                    } else {
+                        Err(KyaroError::parser_error("Invalid f-string token", token.line, token.column))
+                    }
+                }
                 TokenType::KeywordTrue => {
                     self.advance();
                     Ok(ASTNode::Boolean(BooleanNode::new(true)))
@@ -482,8 +942,9 @@ impl Parser {
                 TokenType::Identifier => {
                     if let TokenValue::Identifier(name) = &token.value {
                         let name = name.clone();
+                        let span = Span::new(token.line, token.column);
                         self.advance();
-                        Ok(ASTNode::Identifier(IdentifierNode::new(name)))
+                        Ok(ASTNode::Identifier(IdentifierNode::new(name, span)))
                     } else {
                         Err(KyaroError::parser_error("Invalid identifier token", token.line, token.column))
                     }
@@ -515,6 +976,8 @@ impl Parser {
                     self.expect(TokenType::RBracket)?;
                     Ok(ASTNode::List(ListNode::new(elements)))
                 }
+                TokenType::LBrace => self.parse_dict(),
+                TokenType::KeywordFunc => self.parse_anonymous_function(),
                 _ => Err(KyaroError::parser_error(
                     format!("Unexpected token: {:?}", token.token_type),
                     token.line,
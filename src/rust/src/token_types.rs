@@ -1,10 +1,12 @@
 use std::collections::HashMap;
 use std::fmt;
+use num_bigint::BigInt;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenType {
     Number,
     String,
+    FString,
     Identifier,
     
     // Keywords
@@ -27,12 +29,17 @@ pub enum TokenType {
     KeywordNot,
     KeywordClass,
     KeywordImport,
+    KeywordTry,
+    KeywordCatch,
+    KeywordFinally,
+    KeywordThrow,
     
     // Operators
     Plus,
     Minus,
     Star,
     Slash,
+    SlashSlash,
     Percent,
     Power,
     
@@ -50,6 +57,9 @@ pub enum TokenType {
     MinusEquals,
     StarEquals,
     SlashEquals,
+    SlashSlashEquals,
+    PercentEquals,
+    PowerEquals,
     
     // Delimiters
     LParen,
@@ -71,7 +81,7 @@ pub enum TokenType {
     Eof,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Token {
     pub token_type: TokenType,
     pub value: TokenValue,
@@ -81,13 +91,25 @@ pub struct Token {
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenValue {
-    Number(f64),
+    Int(BigInt),
+    Float(f64),
+    /// The coefficient of an imaginary literal such as `4j`.
+    Imaginary(f64),
     String(String),
     Identifier(String),
     Symbol(String),
+    FString(Vec<FStringSegment>),
     None,
 }
 
+/// A piece of an f-string: literal text, or an embedded expression that is
+/// already tokenized, with the format spec that followed its `:`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FStringSegment {
+    Text(String),
+    Expression { tokens: Vec<Token>, spec: Option<String> },
+}
+
 impl Token {
     pub fn new(token_type: TokenType, value: TokenValue, line: usize, column: usize) -> Self {
         Self {
@@ -98,10 +120,6 @@ impl Token {
         }
     }
     
-    pub fn number(value: f64, line: usize, column: usize) -> Self {
-        Self::new(TokenType::Number, TokenValue::Number(value), line, column)
-    }
-    
     pub fn string(value: String, line: usize, column: usize) -> Self {
         Self::new(TokenType::String, TokenValue::String(value), line, column)
     }
@@ -147,6 +165,10 @@ pub fn create_keywords_map() -> HashMap<String, TokenType> {
     keywords.insert("not".to_string(), TokenType::KeywordNot);
     keywords.insert("class".to_string(), TokenType::KeywordClass);
     keywords.insert("import".to_string(), TokenType::KeywordImport);
+    keywords.insert("try".to_string(), TokenType::KeywordTry);
+    keywords.insert("catch".to_string(), TokenType::KeywordCatch);
+    keywords.insert("finally".to_string(), TokenType::KeywordFinally);
+    keywords.insert("throw".to_string(), TokenType::KeywordThrow);
     
     keywords
 }
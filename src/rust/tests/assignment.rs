@@ -0,0 +1,61 @@
+mod common;
+
+use common::{error, output};
+
+#[test]
+fn compound_assignment_evaluates_target_once() {
+    let source = r#"
+let calls = 0
+func next() {
+    calls += 1
+    return calls - 1
+}
+let xs = [10, 20, 30]
+xs[next()] += 5
+print(xs, calls)
+let d = {"n": 1}
+let ds = [d]
+ds[next() - 1]["n"] *= 7
+print(d, calls)
+"#;
+    assert_eq!(output(source), "[15, 20, 30] 1\n{'n': 7} 2\n");
+}
+
+#[test]
+fn compound_assignment_to_member() {
+    let source = r#"
+class Counter {
+    func init(self) {
+        self.count = 0
+    }
+}
+let c = Counter()
+c.count += 2
+c.count -= 1
+print(c.count)
+"#;
+    assert_eq!(output(source), "1\n");
+}
+
+#[test]
+fn every_arithmetic_operator_has_a_compound_form() {
+    let source = r#"
+let x = 17
+x %= 5
+let y = 2
+y **= 10
+let z = 7.5
+z //= 2
+let xs = [-7, 3]
+xs[0] %= 3
+xs[1] **= 2
+print(x, y, z, xs)
+"#;
+    assert_eq!(output(source), "2 1024 3.0 [2, 9]\n");
+}
+
+#[test]
+fn assignment_requires_existing_binding() {
+    assert!(error("missing = 1").contains("missing"));
+    assert!(error("let xs = [1]\nxs[3] += 1").contains("out of range"));
+}
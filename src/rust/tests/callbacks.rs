@@ -0,0 +1,43 @@
+mod common;
+
+use common::{error, output};
+
+#[test]
+fn functional_builtins_call_kyaro_functions() {
+    assert_eq!(
+        output("func square(x) {\n    return x * x\n}\nprint(map(square, [1, 2, 3]), filter(x -> x % 2 == 0, range(6)))"),
+        "[1, 4, 9] [0, 2, 4]\n"
+    );
+    assert_eq!(output("print(reduce((a, b) -> a + b, [1, 2, 3], 10), reduce((a, b) -> a * b, [2, 3]))"), "16 6\n");
+    assert_eq!(output("print(sorted([\"ccc\", \"a\", \"bb\"], len), sorted([3, 1, 2], x -> -x))"), "['a', 'bb', 'ccc'] [3, 2, 1]\n");
+    assert_eq!(output("print(all(x -> x > 0, [1, 2]), any(x -> x > 1, [1, 2]), all([]), any([0, 0]))"), "True True True False\n");
+    assert_eq!(output("print(all(function: x -> x > 1, list: [1, 2]), any(list: [0, 1]))"), "False True\n");
+    
+    // The list-first order predates the (function, list) order of map and filter
+    assert_eq!(output("print(all([1, 2], x -> x > 1), any([1, 2], x -> x > 1))"), "False True\n");
+}
+
+#[test]
+fn optimizers_call_user_loss_functions() {
+    assert_eq!(output("let w = ml_minimize(w -> (w[0] - 3) ** 2, [0], 0.1, 200)\nprint(round(w[0], 3))"), "3.0\n");
+    assert!(error("ml_minimize(w -> \"x\", [0])").ends_with("ml_minimize() loss function must return a number, not string\n"));
+}
+
+#[test]
+fn callback_errors_are_traced_through_the_builtin() {
+    let report = error("let xs = [1, 2]\nprint(map(x -> x / 0, xs))");
+    let lines: Vec<&str> = report.lines().filter(|line| !line.starts_with("  File")).collect();
+    assert_eq!(
+        lines,
+        [
+            "Traceback (most recent call last):",
+            "    print(map(x -> x / 0, xs))",
+            "    print(map(x -> x / 0, xs))",
+            "Runtime error at line 2, column 18: Division by zero",
+        ]
+    );
+    assert!(report.contains(", line 2, in <lambda>\n"));
+    
+    assert!(error("map(1, [1])").ends_with("Cannot call non-function type: integer\n"));
+    assert!(error("reduce((a, b) -> a + b, [])").ends_with("reduce() of empty list with no initial value\n"));
+}
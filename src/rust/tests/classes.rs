@@ -0,0 +1,67 @@
+mod common;
+
+use common::{error, output};
+
+#[test]
+fn constructor_methods_fields_and_inheritance() {
+    let source = r#"
+class Animal {
+    func init(self, name) {
+        self.name = name
+    }
+    func speak(self) {
+        return self.name + " makes a sound"
+    }
+    func describe(self) {
+        return "I am " + self.name
+    }
+}
+class Dog(Animal) {
+    func speak(self) {
+        return self.name + " barks"
+    }
+}
+let a = Animal("Cat")
+let d = Dog("Rex")
+print(a.speak(), d.speak(), d.describe())
+print(type(d), type(a), d)
+d.age = 3
+d.name = "Max"
+print(d.age, d.speak())
+let m = d.speak
+print(m())
+print(Animal.speak(d))
+"#;
+    assert_eq!(
+        output(source),
+        "Cat makes a sound Rex barks I am Rex\nDog Animal <Dog object>\n3 Max barks\nMax barks\nMax makes a sound\n"
+    );
+}
+
+#[test]
+fn instances_are_shared_references() {
+    let source = r#"
+class Counter {
+    func init(self) {
+        self.count = 0
+    }
+    func bump(self) {
+        self.count += 1
+        return self
+    }
+}
+let c = Counter()
+let same = c
+c.bump().bump()
+print(same.count, c == same, c == Counter())
+"#;
+    assert_eq!(output(source), "2 True False\n");
+}
+
+#[test]
+fn class_errors() {
+    assert!(error("class A {}\nlet a = A()\nprint(a.missing)").contains("'A' object has no attribute 'missing'"));
+    assert!(error("class A {\n    func init(self, x) {\n    }\n}\nA()").contains("Function A.init expects 2 arguments, got 1"));
+    assert!(error("class B(Nope) {}").contains("Undefined variable 'Nope'"));
+    assert!(error("let x = 1\nclass B(x) {}").contains("Superclass of B must be a class, not integer"));
+}
@@ -0,0 +1,40 @@
+//! Runs Kyaro programs through the `kyaro` binary, for features the Python
+//! implementation cannot express and so `tests/test_compatibility.py` cannot cover.
+#![allow(dead_code)]
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// Runs `source` as a script file and returns the process output.
+pub fn run(source: &str) -> Output {
+    let mut file = tempfile::Builder::new().suffix(".kyaro").tempfile().unwrap();
+    file.write_all(source.as_bytes()).unwrap();
+    run_file(file.path())
+}
+
+pub fn run_file(path: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_kyaro")).arg(path).output().unwrap()
+}
+
+/// The standard output of a program that must succeed.
+pub fn output(source: &str) -> String {
+    expect_success(run(source))
+}
+
+/// The error report of a program that must fail.
+pub fn error(source: &str) -> String {
+    expect_failure(run(source))
+}
+
+pub fn expect_success(output: Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "program failed:\n{}", stderr);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+pub fn expect_failure(output: Output) -> String {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1), "program did not fail cleanly:\n{}", stdout);
+    String::from_utf8(output.stderr).unwrap()
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use common::{error, output};
+
+#[test]
+fn imaginary_literals_make_complex_numbers() {
+    assert_eq!(
+        output("let z = 3 + 4j\nprint(z, abs(z), real(z), imag(z), conj(z), type(z))\nprint(f\"{z}\", str(1.5j), [1j], 0j, -z)"),
+        "(3+4j) 5.0 3.0 4.0 (3-4j) complex\n(3+4j) 1.5j [1j] 0j (-3-4j)\n"
+    );
+    assert_eq!(output("print(complex(), complex(2), complex(imag: 5), complex(3, 4) == 3 + 4j, complex(1 + 1j, 1j))"), "0j (2+0j) 5j True 1j\n");
+}
+
+#[test]
+fn arithmetic_mixes_complex_with_other_numbers() {
+    assert_eq!(output("let z = 3 + 4j\nprint(z * 2j, z / 2, (1 + 2j) ** 2, 1j ** 2, z - 3, 1.5 + z)"), "(-8+6j) (1.5+2j) (-3+4j) (-1+0j) 4j (4.5+4j)\n");
+}
+
+#[test]
+fn math_functions_accept_and_return_complex_numbers() {
+    assert_eq!(output("print(sqrt(-4), sqrt(3 + 4j), log(-1), phase(1j), sqrt(4))"), "2j (2+1j) 3.141592653589793j 1.5707963267948966 2.0\n");
+    assert_eq!(output("import math\nprint(math.sqrt(-1), math.conj(1 - 1j))"), "1j (1+1j)\n");
+}
+
+#[test]
+fn unsupported_complex_operations_are_runtime_errors() {
+    assert!(error("print(1j < 2j)").ends_with("'<' not supported between complex and complex\n"));
+    assert!(error("print(1j // 2)").ends_with("Unsupported operand types for //: complex and integer\n"));
+    assert!(error("print(1j % 2)").ends_with("Unsupported operand types for %: complex and integer\n"));
+    assert!(error("print(1j / 0)").ends_with("Division by zero\n"));
+    assert!(error("print(0j ** -1)").ends_with("Zero cannot be raised to a negative or complex power\n"));
+    assert!(error("print(complex(\"a\"))").ends_with("complex() requires numbers, not string\n"));
+}
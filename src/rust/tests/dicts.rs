@@ -0,0 +1,77 @@
+mod common;
+
+use common::{error, output};
+
+#[test]
+fn literals_indexing_and_iteration_keep_insertion_order() {
+    let source = r#"
+let d = {"name": "x", "age": 3}
+d["age"] += 1
+d["city"] = "Oslo"
+for k in d {
+    print(k, d[k])
+}
+print(d, len(d), {})
+"#;
+    assert_eq!(output(source), "name x\nage 4\ncity Oslo\n{'name': 'x', 'age': 4, 'city': 'Oslo'} 3 {}\n");
+}
+
+#[test]
+fn dict_builtins() {
+    let source = r#"
+let d = {"a": 1, "b": 2}
+print(keys(d), values(d), items(d))
+print(get(d, "a"), get(d, "z"), get(d, "z", 0), has_key(d, "b"), has_key(d, "z"))
+"#;
+    assert_eq!(output(source), "['a', 'b'] [1, 2] [['a', 1], ['b', 2]]\n1 None 0 True False\n");
+}
+
+#[test]
+fn equal_numbers_share_a_key() {
+    assert_eq!(output("let n = {1: \"int\", 2.5: \"float\"}\nn[1.0] = \"one\"\nprint(n)"), "{1: 'one', 2.5: 'float'}\n");
+    assert_eq!(
+        output("let c = {1+0j: \"real\", 2j: \"imaginary\"}\nc[1] = \"one\"\nprint(c, c[complex(0, 2)], has_key(c, 1.0))"),
+        "{(1+0j): 'one', 2j: 'imaginary'} imaginary True\n"
+    );
+}
+
+#[test]
+fn booleans_are_not_numeric_keys() {
+    // Unlike Python, where True == 1, Kyaro booleans never equal numbers
+    assert_eq!(output("let d = {true: \"bool\", 1: \"int\", 0: \"zero\"}\nprint(d, d[true], has_key(d, false), true == 1)"), "{True: 'bool', 1: 'int', 0: 'zero'} bool False False\n");
+}
+
+#[test]
+fn dicts_are_shared_and_compare_by_contents() {
+    let source = r#"
+let nested = {"xs": [1, 2], "inner": {"k": "v"}}
+let alias = nested
+append(alias["xs"], 3)
+print(nested["inner"]["k"], nested)
+print({"a": 1, "b": 2} == {"b": 2, "a": 1}, {"a": 1} == {"a": 2})
+"#;
+    assert_eq!(output(source), "v {'xs': [1, 2, 3], 'inner': {'k': 'v'}}\nTrue False\n");
+}
+
+#[test]
+fn self_containing_dict_prints_and_compares() {
+    let source = r#"
+let d = {"a": 1}
+d["self"] = d
+let l = [d]
+d["l"] = l
+print(d, d == d)
+print(l)
+"#;
+    assert_eq!(
+        output(source),
+        "{'a': 1, 'self': {...}, 'l': [{...}]} True\n[{'a': 1, 'self': {...}, 'l': [...]}]\n"
+    );
+}
+
+#[test]
+fn missing_and_unhashable_keys_are_errors() {
+    assert!(error("print({\"a\": 1}[\"b\"])").contains("Key 'b' not found in dict"));
+    assert!(error("let d = {[1]: 2}").contains("Unhashable type: list"));
+    assert!(error("print(get([1], 0))").contains("get() requires a dict"));
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use common::{error, output};
+use laminax_kpl::{Frame, KyaroError};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// The last line of an error report: the error itself, without the traceback.
+fn message(source: &str) -> String {
+    error(source).lines().last().unwrap().to_string()
+}
+
+#[test]
+fn runtime_errors_point_at_the_failing_expression() {
+    assert_eq!(message("let x = 1\nprint(x / 0)"), "Runtime error at line 2, column 9: Division by zero");
+    assert_eq!(
+        message("let xs = [1]\nlet y = xs[5]"),
+        "Runtime error at line 2, column 11: list index 5 out of range (length 1)"
+    );
+    assert_eq!(
+        message("print(undefined_name)"),
+        "Runtime error at line 1, column 7: Undefined variable 'undefined_name'"
+    );
+}
+
+#[test]
+fn errors_inside_functions_keep_their_own_position() {
+    assert_eq!(
+        message("func f() {\n    return 1 + \"a\"\n}\nf()"),
+        "Runtime error at line 2, column 14: Unsupported operand types for +: integer and string"
+    );
+}
+
+#[test]
+fn syntax_errors_are_reported_without_a_traceback() {
+    assert_eq!(error("let s = \"a\" +\n 1").trim_end(), "Parser error at line 1, column 14: Unexpected token: Newline");
+}
+
+#[test]
+fn traceback_lists_calls_outermost_first() {
+    let source = "func inner(x) {\n    return x / 0\n}\nfunc outer() {\n    return inner(1)\n}\nouter()\n";
+    let file = tempfile::Builder::new().suffix(".kyaro").tempfile().unwrap();
+    std::fs::write(file.path(), source).unwrap();
+    let name = file.path().display();
+    
+    let report = common::expect_failure(common::run_file(file.path()));
+    assert_eq!(
+        report,
+        format!(
+            "Traceback (most recent call last):\n  File \"{name}\", line 7, in <module>\n    outer()\n  \
+             File \"{name}\", line 5, in outer\n    return inner(1)\n  File \"{name}\", line 2, in inner\n    \
+             return x / 0\nRuntime error at line 2, column 14: Division by zero\n"
+        )
+    );
+}
+
+#[test]
+fn runaway_recursion_raises_a_catchable_error() {
+    let source = "func depth(n) {\n    if n == 0 {\n        return 0\n    }\n    return 1 + depth(n - 1)\n}\n\
+                  print(depth(900))\ntry {\n    depth(100000)\n} catch err {\n    print(err.kind, err.message)\n}\n\
+                  print(depth(3))\n";
+    assert_eq!(output(source), "900\nruntime maximum recursion depth exceeded\n3\n");
+}
+
+#[test]
+fn recursion_traceback_collapses_repeated_frames() {
+    let report = error("func forever(n) {\n    return forever(n + 1)\n}\nforever(0)\n");
+    let lines: Vec<&str> = report.lines().collect();
+    assert_eq!(lines.len(), 11, "{}", report);
+    assert_eq!(lines[2], "    forever(0)");
+    assert_eq!(lines[8], "    return forever(n + 1)");
+    assert_eq!(lines[9], "  [Previous line repeated 997 more times]");
+    assert_eq!(lines[10], "Runtime error at line 2, column 19: maximum recursion depth exceeded");
+}
+
+#[test]
+fn repl_traceback_quotes_only_the_input_a_frame_belongs_to() {
+    let mut repl = Command::new(env!("CARGO_BIN_EXE_kyaro"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    repl.stdin
+        .take()
+        .unwrap()
+        .write_all(b"func f(x) { return x / 0 }\nlet unrelated = 1\nf(1)\nexit()\n")
+        .unwrap();
+    let output = repl.wait_with_output().unwrap();
+    
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "Traceback (most recent call last):\n  File \"<input-3>\", line 1, in <module>\n    f(1)\n  \
+         File \"<input-1>\", line 1, in f\nRuntime error at line 1, column 22: Division by zero\n"
+    );
+}
+
+#[test]
+fn unpositioned_frames_are_reported_without_an_excerpt() {
+    let error = KyaroError::runtime_error("boom", 1, 1).with_traceback(&[Frame::new("callback", None, 0, 0)], None);
+    assert_eq!(
+        error.report("explode()", "main.kyaro"),
+        "Traceback (most recent call last):\n  File \"main.kyaro\", line 0, in <module>\n  \
+         File \"main.kyaro\", line 1, in callback\n    explode()\nRuntime error at line 1, column 1: boom"
+    );
+}
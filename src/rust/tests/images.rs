@@ -0,0 +1,94 @@
+mod common;
+
+use common::{error, output};
+
+/// A 3x2 RGB image, dark blue except for a red top left pixel.
+const IMAGE: &str = "let img = image_new(\"RGB\", [3, 2], [10, 20, 30])\nimage_put_pixel(img, [0, 0], [255, 0, 0])\n";
+
+fn run(source: &str) -> String {
+    output(&format!("{}{}", IMAGE, source))
+}
+
+#[test]
+fn images_know_their_mode_size_and_pixels() {
+    assert_eq!(
+        run("print(img, image_mode(img), image_size(img), type(img))\nprint(image_get_pixel(img, [0, 0]), image_get_pixel(img, [2, 1]))"),
+        "<image RGB 3x2> RGB [3, 2] image\n[255, 0, 0] [10, 20, 30]\n"
+    );
+    assert_eq!(output("let img = image_new(\"L\", [2, 2], 7)\nprint(image_get_pixel(img, [1, 1]))"), "7\n");
+    assert_eq!(output("print(image_get_pixel(image_new(\"RGBA\", [1, 1], [1, 2, 3]), [0, 0]))"), "[1, 2, 3, 255]\n");
+    assert_eq!(run("let copy = image_copy(img)\nimage_put_pixel(copy, [0, 0], [0, 0, 0])\nprint(image_get_pixel(img, [0, 0]), img == copy)"), "[255, 0, 0] False\n");
+}
+
+#[test]
+fn converting_to_grayscale_uses_pil_luma() {
+    assert_eq!(
+        run("let gray = image_grayscale(img)\nprint(gray, image_get_pixel(gray, [0, 0]), image_get_pixel(gray, [1, 0]))"),
+        "<image L 3x2> 76 18\n"
+    );
+    assert_eq!(run("print(image_get_pixel(image_convert(img, \"LA\"), [0, 0]))"), "[76, 255]\n");
+}
+
+#[test]
+fn geometry_moves_pixels() {
+    assert_eq!(
+        run("let flipped = image_flip_horizontal(img)\nprint(image_get_pixel(flipped, [2, 0]), image_get_pixel(image_flip_vertical(img), [0, 1]))"),
+        "[255, 0, 0] [255, 0, 0]\n"
+    );
+    assert_eq!(run("let t = image_transpose(img)\nprint(image_size(t), image_get_pixel(t, [0, 0]))"), "[2, 3] [255, 0, 0]\n");
+    // Rotation is counter-clockwise; without expand the size stays the same
+    assert_eq!(
+        run("let r = image_rotate(img, 90, true)\nprint(image_size(r), image_get_pixel(r, [0, 2]))\nprint(image_size(image_rotate(img, 45)))"),
+        "[2, 3] [255, 0, 0]\n[3, 2]\n"
+    );
+    assert_eq!(
+        output("let img = image_new(\"L\", [5, 5], 0)\nimage_put_pixel(img, [4, 2], 9)\nlet r = image_rotate(img, 89.9)\nprint(image_get_pixel(r, [2, 0]), image_get_pixel(image_rotate(img, 45, false, 1), [0, 0]))"),
+        "9 1\n"
+    );
+    // Crop boxes may reach outside the image, which is filled with zeros
+    assert_eq!(
+        run("let c = image_crop(img, [-1, 0, 2, 1])\nprint(image_size(c), image_get_pixel(c, [0, 0]), image_get_pixel(c, [1, 0]))"),
+        "[3, 1] [0, 0, 0] [255, 0, 0]\n"
+    );
+    assert_eq!(run("print(image_size(image_resize(img, [6, 4])))\nimage_thumbnail(img, [2, 8])\nprint(image_size(img))"), "[6, 4]\n[2, 1]\n");
+}
+
+#[test]
+fn filters_and_composition_follow_pil() {
+    assert_eq!(
+        run("print(image_get_pixel(image_invert(img), [1, 1]), image_get_pixel(image_brightness(img, 2), [1, 1]))"),
+        "[245, 235, 225] [20, 40, 60]\n"
+    );
+    assert_eq!(run("print(image_get_pixel(image_blend(img, image_invert(img), 0.5), [1, 1]))"), "[127, 127, 127]\n");
+    assert_eq!(run("print(image_get_pixel(image_contrast(image_grayscale(img), 0), [0, 0]))"), "28\n");
+    assert_eq!(run("print(image_size(image_blur(img, radius: 1)))"), "[3, 2]\n");
+    assert_eq!(
+        run("let canvas = image_new(\"RGBA\", [4, 4])\nimage_paste(canvas, img, [1, 2])\nprint(image_get_pixel(canvas, [1, 2]), image_get_pixel(canvas, [0, 0]))"),
+        "[255, 0, 0, 255] [0, 0, 0, 0]\n"
+    );
+}
+
+#[test]
+fn images_round_trip_through_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("picture.png");
+    let source = format!(
+        "import image\nimage.save(img, \"{0}\")\nlet loaded = image.load(\"{0}\")\nprint(loaded, image.get_pixel(loaded, [0, 0]))",
+        path.display()
+    );
+    
+    assert_eq!(run(&source), "<image RGB 3x2> [255, 0, 0]\n");
+    assert!(error(&format!("image_load(\"{}\")", dir.path().join("missing.png").display())).contains("Cannot load image:"));
+}
+
+#[test]
+fn invalid_image_arguments_are_runtime_errors() {
+    assert!(error("image_new(\"CMYK\", [1, 1])").ends_with("image_new() does not support image mode 'CMYK' (use L, LA, RGB or RGBA)\n"));
+    assert!(error("image_new(\"L\", [0, 1])").ends_with("image_new() requires a positive width and height, got 0x1\n"));
+    assert!(error("image_new(\"RGB\", [1, 1], [1, 2])")
+        .ends_with("image_new() requires a color as an integer or a list of 3 integers from 0 to 255\n"));
+    assert!(error(&format!("{}image_get_pixel(img, [3, 0])", IMAGE)).ends_with("image_get_pixel() point [3, 0] is outside the 3x2 image\n"));
+    assert!(error(&format!("{}image_blend(img, image_grayscale(img), 0.5)", IMAGE))
+        .ends_with("image_blend() requires images of the same mode and size\n"));
+    assert!(error("image_size([1, 2])").ends_with("image_size() requires an image\n"));
+}
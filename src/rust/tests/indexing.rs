@@ -0,0 +1,29 @@
+mod common;
+
+use common::{error, output};
+
+#[test]
+fn negative_indices_and_slices() {
+    let source = r#"
+let xs = [1, 2, 3, 4, 5, 6]
+print(xs[-1], xs[1:3], xs[:2], xs[4:], xs[::2], xs[::-1], xs[-2:])
+print("kyaro"[1:-1], "kyaro"[::-1])
+"#;
+    assert_eq!(output(source), "6 [2, 3] [1, 2] [5, 6] [1, 3, 5] [6, 5, 4, 3, 2, 1] [5, 6]\nyar orayk\n");
+}
+
+#[test]
+fn slice_with_huge_step_stops_at_end() {
+    let source = r#"
+let xs = [1, 2, 3, 4, 5, 6]
+print(xs[0:5:9223372036854775807], xs[::-9223372036854775807], "abc"[1::9223372036854775807])
+"#;
+    assert_eq!(output(source), "[1] [6] b\n");
+}
+
+#[test]
+fn out_of_range_index_is_an_error() {
+    assert!(error("let xs = [1, 2]\nprint(xs[2])").contains("list index 2 out of range (length 2)"));
+    assert!(error("print(\"ab\"[-3])").contains("string index -3 out of range (length 2)"));
+    assert!(error("print([1, 2][::0])").contains("Slice step cannot be zero"));
+}
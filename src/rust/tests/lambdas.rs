@@ -0,0 +1,28 @@
+mod common;
+
+use common::{error, output};
+
+#[test]
+fn arrow_and_anonymous_functions_are_values() {
+    let source = "let double = x -> x * 2\nlet add = (a, b) -> a + b\nlet seven = () -> 7\n\
+                  let increment = func(x) {\n    return x + 1\n}\n\
+                  print(double(4), add(2, 3), seven(), increment(1), ((x) -> x - 1)(1))\nprint(type(double), double)";
+    assert_eq!(output(source), "8 5 7 2 0\nfunction <function <lambda>>\n");
+}
+
+#[test]
+fn lambdas_close_over_their_scope() {
+    let source = "let base = 10\nlet offset = x -> x + base\nbase = 20\nprint(offset(1))\n\
+                  func make_counter() {\n    let count = 0\n    return func() {\n        count += 1\n        return count\n    }\n}\n\
+                  let counter = make_counter()\ncounter()\nprint(counter(), make_counter()())";
+    assert_eq!(output(source), "21\n2 1\n");
+}
+
+#[test]
+fn lambda_errors_name_the_lambda() {
+    assert!(error("let f = (a, b) -> a\nf(1)").ends_with("Function <lambda> expects 2 arguments, got 1\n"));
+    
+    let report = error("let f = x -> x / 0\nf(1)");
+    assert!(report.contains(", line 1, in <lambda>\n    let f = x -> x / 0\n"), "{}", report);
+    assert!(report.ends_with("Runtime error at line 1, column 16: Division by zero\n"));
+}
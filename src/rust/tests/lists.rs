@@ -0,0 +1,44 @@
+mod common;
+
+use common::{error, output};
+
+#[test]
+fn builtins_mutate_shared_lists() {
+    let source = r#"
+let xs = [3, 1, 2]
+let alias = xs
+append(xs, 4)
+insert(xs, 0, 0)
+remove(xs, 1)
+sort(alias)
+print(xs, pop(xs), xs)
+reverse(xs)
+extend(xs, xs)
+print(alias)
+clear(alias)
+print(xs)
+"#;
+    assert_eq!(output(source), "[0, 2, 3] 4 [0, 2, 3]\n[3, 2, 0, 3, 2, 0]\n[]\n");
+}
+
+#[test]
+fn self_containing_list_prints_and_compares() {
+    let source = r#"
+let a = [1]
+append(a, a)
+print(a, str(a), len(a))
+let b = [0]
+b[0] = b
+print(b, b == b, [b] == [b], b != b)
+let c = [1]
+append(c, c)
+print(a == c)
+"#;
+    assert_eq!(output(source), "[1, [...]] [1, [...]] 2\n[[...]] True True False\nTrue\n");
+}
+
+#[test]
+fn remove_compares_against_the_list_itself() {
+    assert!(error("let a = [[1], [2]]\nremove(a, a)").contains("remove(): value not in list"));
+    assert_eq!(output("let a = [[1], [2]]\nremove(a, [2])\nprint(a)"), "[[1]]\n");
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::{expect_failure, expect_success, run_file};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Writes `files` (relative path, contents) into a fresh directory.
+fn project(files: &[(&str, &str)]) -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for (path, contents) in files {
+        let path = dir.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn path_and_named_imports_run_each_module_once() {
+    let dir = project(&[
+        ("main.kyaro", "import \"lib/geometry.kyaro\"\nimport helpers\nimport helpers\nprint(geometry.area(2))\nprint(helpers.greet(\"Kyaro\"), helpers.count)\n"),
+        ("lib/geometry.kyaro", "print(\"loading geometry\")\nfunc area(r) {\n    return 3 * r * r\n}\n"),
+        ("helpers.kyaro", "print(\"loading helpers\")\nlet count = 1\nfunc greet(name) {\n    count += 1\n    return \"Hello \" + name\n}\n"),
+    ]);
+    
+    let output = expect_success(run_file(&dir.path().join("main.kyaro")));
+    assert_eq!(output, "loading geometry\nloading helpers\n12\nHello Kyaro 2\n");
+}
+
+#[test]
+fn named_imports_search_kyaro_path() {
+    let dir = project(&[("main.kyaro", "import shared\nprint(shared.value)\n"), ("libs/shared.kyaro", "let value = 42\n")]);
+    
+    let output = Command::new(env!("CARGO_BIN_EXE_kyaro"))
+        .arg(dir.path().join("main.kyaro"))
+        .env("KYARO_PATH", dir.path().join("libs"))
+        .output()
+        .unwrap();
+    assert_eq!(expect_success(output), "42\n");
+}
+
+#[test]
+fn errors_in_modules_are_traced_through_the_import() {
+    let dir = project(&[("main.kyaro", "import broken\n"), ("broken.kyaro", "let x = 1\nprint(x / 0)\n")]);
+    let main = dir.path().join("main.kyaro");
+    
+    let report = expect_failure(run_file(&main));
+    let broken = Path::new(&main).with_file_name("broken.kyaro");
+    assert_eq!(
+        report,
+        format!(
+            "Traceback (most recent call last):\n  File \"{}\", line 1, in <module>\n    import broken\n  \
+             File \"{}\", line 2, in <module>\n    print(x / 0)\nRuntime error at line 2, column 9: Division by zero\n",
+            main.display(),
+            broken.display()
+        )
+    );
+}
+
+#[test]
+fn missing_and_circular_imports_are_errors() {
+    let dir = project(&[
+        ("missing.kyaro", "import nowhere\n"),
+        ("a.kyaro", "import b\n"),
+        ("b.kyaro", "import a\n"),
+    ]);
+    
+    let report = expect_failure(run_file(&dir.path().join("missing.kyaro")));
+    assert!(report.contains("Cannot find module 'nowhere'"), "{}", report);
+    let report = expect_failure(run_file(&dir.path().join("a.kyaro")));
+    assert!(report.contains("Circular import"), "{}", report);
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use common::{error, output};
+use laminax_kpl::{Environment, Interpreter, Lexer, Module, NativeFn, Parser, Result, Value};
+use std::fs;
+use std::rc::Rc;
+
+#[test]
+fn native_modules_drop_the_flat_name_prefix() {
+    assert_eq!(output("import math\nprint(math.sqrt(16), math.pi == pi())"), "4.0 True\n");
+    assert_eq!(output("import ml\nprint(ml.mse([1, 2], [1, 4]) == ml_mse([1, 2], [1, 4]))"), "True\n");
+    assert_eq!(output("import image\nprint(image.size(image.new(\"L\", [3, 2])))"), "[3, 2]\n");
+}
+
+#[test]
+fn file_system_functions_round_trip_through_a_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("notes.txt");
+    let source = format!(
+        "import fs\n\
+         let path = \"{}\"\n\
+         print(fs.exists(path))\n\
+         fs.write_file(path, \"one\\n\")\n\
+         fs_append_file(path, \"two\\n\")\n\
+         print(fs.exists(path), fs.is_file(path), fs.is_dir(path), fs.get_size(path))\n\
+         print(fs.read_lines(path))\n\
+         print(fs.list_dir(fs.path_dirname(path)), fs.get_extension(path), fs.get_stem(path))\n\
+         fs.delete_file(path)\n\
+         print(fs.exists(path))",
+        path.display()
+    );
+    
+    assert_eq!(output(&source), "False\nTrue True False 8\n['one\n', 'two\n']\n['notes.txt'] .txt notes\nFalse\n");
+    assert!(!path.exists());
+}
+
+#[test]
+fn file_system_errors_are_runtime_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("missing.txt");
+    fs::create_dir(dir.path().join("sub")).unwrap();
+    
+    let report = error(&format!("fs_read_file(\"{}\")", missing.display()));
+    assert!(report.contains("Runtime error at line 1, column 13: Cannot read file:"), "{}", report);
+    assert!(error("fs_write_file(1, \"x\")").ends_with("fs_write_file() requires a string path\n"));
+    assert!(error("fs_exists()").ends_with("fs_exists() takes exactly 1 argument\n"));
+}
+
+fn answer(_args: &[Value]) -> Result<Value> {
+    Ok(Value::int(42))
+}
+
+fn interpret(interpreter: &mut Interpreter, source: &str) -> Result<Option<Value>> {
+    let tokens = Lexer::new(source).tokenize()?;
+    interpreter.interpret(Parser::new(tokens).parse()?)
+}
+
+#[test]
+fn hosts_can_register_native_modules() {
+    let mut scope = Environment::new();
+    scope.define("answer".to_string(), Value::NativeFunction { name: "answer".to_string(), function: NativeFn::Plain(answer) });
+    let module = Value::Module(Rc::new(Module { name: "host".to_string(), scope: scope.into_ref() }));
+    
+    let mut interpreter = Interpreter::new();
+    interpreter.register_native_module("host", module.clone());
+    // Registered modules shadow the built-in ones
+    interpreter.register_native_module("math", Value::int(7));
+    
+    interpret(&mut interpreter, "import host\nimport math\nlet result = host.answer() + math").unwrap();
+    assert_eq!(interpret(&mut interpreter, "result").unwrap(), Some(Value::int(49)));
+    assert_eq!(interpret(&mut interpreter, "host").unwrap(), Some(module));
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use common::{error, output};
+
+#[test]
+fn integer_literals_in_every_base() {
+    assert_eq!(output("print(0xff, 0XFF, 0x_ff, 0b1010, 0o17, 0O7_7)"), "255 255 255 10 15 63\n");
+    assert_eq!(output("print(1_000_000, 0, 00, 0_0, 123456789012345678901234567890)"), "1000000 0 0 0 123456789012345678901234567890\n");
+}
+
+#[test]
+fn float_and_imaginary_literals() {
+    assert_eq!(output("print(1.5e3, 2E-2, 1e+2, 1_0.2_5, 3.)"), "1500.0 0.02 100.0 10.25 3.0\n");
+    // Leading zeros are fine outside decimal integers, as in Python
+    assert_eq!(output("print(08.5, 08e1, 08j, 0.5)"), "8.5 80.0 8j 0.5\n");
+}
+
+#[test]
+fn malformed_literals_are_lexer_errors() {
+    let cases = [
+        ("08", "line 1, column 7: Invalid number literal '08': leading zeros are not allowed (use 0o for octal)"),
+        ("0_7", "line 1, column 7: Invalid number literal '07': leading zeros are not allowed (use 0o for octal)"),
+        ("0x", "line 1, column 7: Invalid hexadecimal literal: expected digits after '0x'"),
+        ("0o", "line 1, column 7: Invalid octal literal: expected digits after '0o'"),
+        ("1__0", "line 1, column 8: Invalid number literal: '_' must separate digits"),
+        ("1_", "line 1, column 8: Invalid number literal: '_' must separate digits"),
+        ("1e", "line 1, column 8: Invalid number literal '1e': exponent has no digits"),
+        ("1e+", "line 1, column 8: Invalid number literal '1e+': exponent has no digits"),
+        ("0b102", "line 1, column 11: Invalid digit '2' in binary literal"),
+        ("0xg", "line 1, column 9: Invalid digit 'g' in hexadecimal literal"),
+        ("12abc", "line 1, column 9: Invalid character 'a' in number literal"),
+    ];
+    for (literal, message) in cases {
+        assert_eq!(error(&format!("print({})", literal)), format!("Lexer error at {}\n", message), "{}", literal);
+    }
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use common::{error, output};
+
+#[test]
+fn defaults_fill_missing_arguments_and_are_evaluated_once() {
+    let source = "let calls = 0\nfunc next_id() {\n    calls += 1\n    return calls\n}\n\
+                  func tag(name, id = next_id(), suffix = \"!\") {\n    return name + str(id) + suffix\n}\n\
+                  print(tag(\"a\"), tag(\"b\"), tag(\"c\", 7), tag(\"d\", 8, \"?\"), calls)";
+    assert_eq!(output(source), "a1! b1! c7! d8? 1\n");
+}
+
+#[test]
+fn rest_and_keyword_parameters_collect_extra_arguments() {
+    let source = "func connect(host, port = 8080, *tags, **options) {\n    print(host, port, tags, options)\n}\n\
+                  connect(\"localhost\")\nconnect(\"localhost\", 1, \"a\", \"b\")\nconnect(\"localhost\", port: 9000, retry: 3)\n\
+                  connect(port: 1, host: \"h\")";
+    assert_eq!(
+        output(source),
+        "localhost 8080 [] {}\nlocalhost 1 ['a', 'b'] {}\nlocalhost 9000 [] {'retry': 3}\nh 1 [] {}\n"
+    );
+    // Parameters after *rest can only be passed by name
+    assert_eq!(output("func f(*xs, scale = 1) {\n    return sum(xs) * scale\n}\nprint(f(1, 2), f(1, 2, scale: 10))"), "3 30\n");
+}
+
+#[test]
+fn builtins_accept_keyword_arguments() {
+    assert_eq!(output("print(sorted([\"ccc\", \"a\", \"bb\"], key: len), round(x: 2.567, digits: 1))"), "['a', 'bb', 'ccc'] 2.6\n");
+    assert_eq!(output("print(get({\"a\": 1}, \"b\", default: 0), pop([1, 2, 3], index: 0))"), "0 1\n");
+}
+
+#[test]
+fn mismatched_arguments_are_runtime_errors() {
+    assert!(error("func f(a) {\n}\nf()").ends_with("Function f expects 1 argument, got 0\n"));
+    assert!(error("func f(a, b = 1) {\n}\nf()").ends_with("Function f expects 1 to 2 arguments, got 0\n"));
+    assert!(error("func f(a, *rest) {\n}\nf()").ends_with("Function f expects at least 1 argument, got 0\n"));
+    assert!(error("func f(a, b) {\n}\nf(1, 2, 3)").ends_with("Function f expects 2 arguments, got 3\n"));
+    assert!(error("func f(a, b) {\n}\nf(b: 1)").ends_with("Function f missing argument 'a'\n"));
+    assert!(error("func f(a) {\n}\nf(1, a: 2)").ends_with("Function f got multiple values for argument 'a'\n"));
+    assert!(error("func f(a) {\n}\nf(b: 2)").ends_with("Function f got an unexpected keyword argument 'b'\n"));
+    
+    // Builtins may only skip their optional parameters
+    assert!(error("print(replace(\"aaa\", \"a\", count: 1))").ends_with("replace() missing argument 'new'\n"));
+    assert!(error("print(round(1.5, places: 1))").ends_with("round() got an unexpected keyword argument 'places'\n"));
+}
+
+#[test]
+fn natives_without_parameter_names_reject_keywords() {
+    assert!(error("print(len(x: [1]))").ends_with("len() does not accept keyword arguments\n"));
+    assert!(error("print(sqrt(x: 4))").ends_with("sqrt() does not accept keyword arguments\n"));
+    assert!(error("print(range(stop: 3))").ends_with("range() does not accept keyword arguments\n"));
+    assert!(error("import math\nprint(math.sqrt(x: 4))").ends_with("sqrt() does not accept keyword arguments\n"));
+    assert_eq!(output("print(sqrt(4), range(3))"), "2.0 [0, 1, 2]\n");
+}
+
+#[test]
+fn builtins_pass_skipped_optional_parameters_as_null() {
+    assert_eq!(output("print(complex(imag: 2), ml_minimize(w -> w[0] ** 2, [1], iterations: 0))"), "2j [1.0]\n");
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::{error, output};
+
+#[test]
+fn f_strings_evaluate_expressions_in_scope() {
+    assert_eq!(
+        output("let name = \"Kyaro\"\nfunc shout(s) {\n    return upper(s)\n}\nprint(f\"Hello {name}, you are {len(name) + 1}, {shout(name)}! {{braces}}\")"),
+        "Hello Kyaro, you are 6, KYARO! {braces}\n"
+    );
+    assert_eq!(output("print(f\"{true} {null} {[1, 2]} {1 / 2}\")"), "True None [1, 2] 0.5\n");
+}
+
+#[test]
+fn format_specs_follow_python() {
+    assert_eq!(output("let price = 3.14159\nprint(f\"{price:.2f}|{42:>5}|{\"ab\":*^6}|{7:<3}|{-42:=6}\")"), "3.14|   42|**ab**|7  |-   42\n");
+    assert_eq!(
+        output("print(f\"{1234567:,} {255:#x} {255:08b} {5:+d} {0.25:.1%} {1234.5:.3e} {3:.3f} {3:.3g} {\"Kyaro\":.2}\")"),
+        "1,234,567 0xff 11111111 +5 25.0% 1.234e+03 3.000 3 Ky\n"
+    );
+    assert_eq!(output("print(format(3.14159, \".3\"), format(10, \"x\"), format(1000000, \"_\"))"), "3.14 a 1_000_000\n");
+}
+
+#[test]
+fn format_specs_handle_signed_zero_and_non_finite_floats() {
+    assert_eq!(output("print(f\"{-0.0:.1f} {-0.0} {-0.0001:.1f} {0.0:+.1f}\")"), "-0.0 -0.0 -0.0 +0.0\n");
+    assert_eq!(
+        output("let inf = 1e308 * 10\nlet nan = inf - inf\nprint(f\"{inf:f} {-inf:.2f} {nan:e} {inf:g} {nan:F} {inf:E} {nan:%}\")"),
+        "inf -inf nan inf NAN INF nan%\n"
+    );
+}
+
+#[test]
+fn zero_padding_is_grouped_with_the_digits() {
+    assert_eq!(
+        output("print(f\"{1234:010,} {1234:08,} {-1234:010,} {1234.5:012,.2f} {255:#012_x} {1234:*>10,}\")"),
+        "00,001,234 0,001,234 -0,001,234 0,001,234.50 0x0_0000_00ff *****1,234\n"
+    );
+}
+
+#[test]
+fn invalid_format_specs_are_runtime_errors() {
+    assert!(error("print(f\"{3:.3}\")").ends_with("Precision not allowed in integer format specifier\n"));
+    assert!(error("print(format(3, \".2x\"))").ends_with("Precision not allowed in integer format specifier\n"));
+    assert!(error("print(f\"{1.5:d}\")").ends_with("Format code 'd' requires an integer\n"));
+    assert!(error("print(f\"{1:q}\")").ends_with("Unknown format code 'q' for number\n"));
+    assert!(error("print(f\"{\"a\":=5}\")").ends_with("Invalid format specifier '=5'\n"));
+    assert!(error("print(f\"{1:10000000000000000000}\")").ends_with("Too many decimal digits in format string\n"));
+    assert!(error("print(format(1.5, \".2000000f\"))").ends_with("Too many decimal digits in format string\n"));
+    assert!(error("print(f\"{[1, 2]:>10}\")").ends_with("unsupported format spec for list\n"));
+    assert!(error("print(format(3+4j, \"10\"))").ends_with("unsupported format spec for complex\n"));
+    assert_eq!(output("print(f\"{[1, 2]:} {3+4j:}\")"), "[1, 2] (3+4j)\n");
+}
+
+#[test]
+fn malformed_f_strings_are_lexer_errors() {
+    assert_eq!(error("print(f\"{name\")"), "Lexer error at line 1, column 10: Unterminated expression in f-string\n");
+    assert_eq!(error("print(f\"{}\")"), "Lexer error at line 1, column 10: Empty expression in f-string\n");
+    assert_eq!(error("print(f\"}\")"), "Lexer error at line 1, column 9: Single '}' is not allowed in f-string\n");
+}
+
+#[test]
+fn triple_quoted_and_raw_strings() {
+    assert_eq!(output("let banner = \"\"\"\nline one\n  line \"two\"\n\"\"\"\nprint(banner)"), "\nline one\n  line \"two\"\n\n");
+    assert_eq!(output("let name = \"Kyaro\"\nprint(r\"\\d+\\.\\d+\", rf\"{name}\\n\", \"a\\tb\")"), "\\d+\\.\\d+ Kyaro\\n a\tb\n");
+    assert_eq!(error("print(\"\"\"open)"), "Lexer error at line 1, column 7: Unterminated string\n");
+}
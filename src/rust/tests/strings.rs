@@ -0,0 +1,57 @@
+mod common;
+
+use common::{error, output};
+
+#[test]
+fn graphemes_keep_clusters_together() {
+    // A combining accent, a flag pair and a family joined by zero-width joiners
+    assert_eq!(
+        output("let s = \"e\u{301}🇯🇵👨\u{200d}👩\u{200d}👧!\"\nprint(len(graphemes(s)), len(chars(s)))\nprint(graphemes(s)[1] == \"🇯🇵\")"),
+        "4 10\nTrue\n"
+    );
+    assert_eq!(output("print(graphemes(\"\"), graphemes(\"ab\"))"), "[] ['a', 'b']\n");
+}
+
+#[test]
+fn pad_left_and_pad_right_fill_to_a_width() {
+    assert_eq!(
+        output("print(f\"[{pad_left(\"7\", 3)}] [{pad_right(\"ab\", 4)}] [{pad_left(\"42\", 5, \"0\")}] [{pad_right(\"x\", 3, fill: \"·\")}]\")"),
+        "[  7] [ab  ] [00042] [x··]\n"
+    );
+    assert_eq!(output("print(pad_left(\"long\", 2), pad_right(\"é\", 3, \"-\"))"), "long é--\n");
+    assert!(error("pad_left(\"a\", 3, \"ab\")").ends_with("pad_left() fill must be exactly one character\n"));
+    assert!(error("pad_right(\"a\", \"3\")").ends_with("pad_right() width must be an integer\n"));
+}
+
+#[test]
+fn bytes_are_the_utf8_encoding() {
+    assert_eq!(output("print(bytes(\"Hi\"), bytes(\"é\"), bytes(\"\"))"), "[72, 105] [195, 169] []\n");
+}
+
+#[test]
+fn find_counts_characters_and_returns_minus_one_when_missing() {
+    assert_eq!(
+        output("print(find(\"hello\", \"l\"), find(\"héllo\", \"llo\"), find(\"hello\", \"z\"), find(\"hello\", \"\"))"),
+        "2 2 -1 0\n"
+    );
+}
+
+#[test]
+fn title_capitalizes_each_word() {
+    assert_eq!(output("print(title(\"hello wORLD\"), title(\"they're o'neil\"), title(\"élan 2nd\"))"), "Hello World They'Re O'Neil Élan 2Nd\n");
+}
+
+#[test]
+fn strip_removes_whitespace_or_a_character_set() {
+    assert_eq!(output("print(\"[\" + strip(\"  \\t hi \\n\") + \"]\")"), "[hi]\n");
+    assert_eq!(output("print(strip(\"xxhixyx\", \"xy\"), strip(\"--a-b--\", \"-\"), strip(\"abc\", \"\"))"), "hi a-b abc\n");
+}
+
+#[test]
+fn replace_takes_an_optional_count() {
+    assert_eq!(
+        output("print(replace(\"a-b-c\", \"-\", \"+\"), replace(\"a-b-c\", \"-\", \"+\", 1), replace(\"a-b-c\", \"-\", \"\", -1), replace(\"abc\", \"z\", \"y\"))"),
+        "a+b+c a+b-c abc abc\n"
+    );
+    assert!(error("replace(\"a\", \"a\", \"b\", \"1\")").ends_with("replace() count must be an integer\n"));
+}
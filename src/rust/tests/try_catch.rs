@@ -0,0 +1,47 @@
+mod common;
+
+use common::{error, output};
+
+#[test]
+fn caught_errors_have_kind_message_and_position() {
+    assert_eq!(
+        output("try {\n    print(1 / 0)\n} catch err {\n    print(err.kind, err.message, err.line, err.column, type(err))\n}"),
+        "runtime Division by zero 2 13 Error\n"
+    );
+    assert_eq!(
+        output("try {\n    throw Error(\"bad input\")\n} catch err {\n    print(err.kind, err.message, err.line, err.column)\n}"),
+        "user bad input 2 5\n"
+    );
+    // Values that are not errors become the message of a user error
+    assert_eq!(output("try {\n    throw 42\n} catch err {\n    print(type(err), err.message, err.kind)\n}"), "Error 42 user\n");
+}
+
+#[test]
+fn thrown_error_subclasses_are_caught_unchanged() {
+    let source = "class ValidationError(Error) {\n    func init(self, field) {\n        self.message = field + \" is invalid\"\n        self.field = field\n    }\n}\n\
+                  let thrown = ValidationError(\"age\")\n\
+                  try {\n    throw thrown\n} catch err {\n    print(err == thrown, type(err), err.field, err.message, err.kind, err.line)\n}";
+    assert_eq!(output(source), "True ValidationError age age is invalid user 9\n");
+}
+
+#[test]
+fn rethrown_errors_keep_their_identity_and_position() {
+    let source = "let first = null\ntry {\n    try {\n        throw Error(\"inner\")\n    } catch err {\n        first = err\n        throw err\n    }\n\
+                  } catch again {\n    print(again == first, again.line, again.column)\n}";
+    assert_eq!(output(source), "True 4 9\n");
+}
+
+#[test]
+fn finally_runs_on_every_exit() {
+    let source = "func risky(n) {\n    try {\n        if n > 1 {\n            return \"big\"\n        }\n        throw Error(\"small\")\n    } finally {\n        print(\"cleanup\", n)\n    }\n}\n\
+                  print(risky(2))\ntry {\n    risky(0)\n} catch {\n    print(\"caught\")\n} finally {\n    print(\"done\")\n}";
+    assert_eq!(output(source), "cleanup 2\nbig\ncleanup 0\ncaught\ndone\n");
+}
+
+#[test]
+fn uncaught_throws_are_reported_with_a_traceback() {
+    let report = error("func check() {\n    throw Error(\"boom\")\n}\ncheck()");
+    let lines: Vec<&str> = report.lines().filter(|line| !line.starts_with("  File")).collect();
+    assert_eq!(lines, ["Traceback (most recent call last):", "    check()", "    throw Error(\"boom\")", "Error at line 2, column 5: boom"]);
+    assert!(error("throw \"plain\"").ends_with("Error at line 1, column 1: plain\n"));
+}
@@ -71,6 +71,315 @@ print(x < y)
 print(x > y)
 print(x == y)
 print(x != y)
+"""
+    },
+    {
+        "name": "Built-in functions",
+        "code": """
+print(sqrt(2))
+print(abs(-7))
+print(max(3, 9, 2))
+print(len("hello"))
+print(mean([1, 2, 3, 4]))
+print(mean([1, 2, 3]), median([3, 1, 2]), median([1.5, 0.5]))
+print("a", 1, true)
+"""
+    },
+    {
+        "name": "If / elif / else",
+        "code": """
+func classify(age) {
+    if age > 17 {
+        print("Adult")
+    } elif age > 12 {
+        print("Teenager")
+    } elif age > 2 {
+        print("Child")
+    } else {
+        print("Toddler")
+    }
+}
+classify(30)
+classify(15)
+classify(5)
+classify(1)
+
+let x = 3
+if x == 1 {
+    print("one")
+} elif x == 2 {
+    print("two")
+}
+if x > 0 {
+    if x > 2 {
+        print("big")
+    } else {
+        print("small")
+    }
+}
+print("done")
+"""
+    },
+    {
+        "name": "Assignment and compound assignment",
+        "code": """
+let i = 0
+while i < 5 {
+    i += 1
+}
+print(i)
+let total = 10
+total -= 4
+total *= 3
+print(total)
+total = total + 1
+print(total)
+let s = "Ky"
+s += "aro"
+print(s)
+"""
+    },
+    {
+        "name": "Return, break and continue",
+        "code": """
+func fib(n) {
+    if n < 2 {
+        return n
+    }
+    return fib(n - 1) + fib(n - 2)
+}
+print(fib(15))
+
+func first_power(base, limit) {
+    let n = 1
+    while true {
+        if n > limit {
+            return n
+        }
+        n *= base
+    }
+}
+print(first_power(2, 100))
+print(first_power(3, 1))
+
+func nothing() {
+    let a = 1
+}
+print(nothing())
+
+let n = 0
+let odds = 0
+while true {
+    n += 1
+    if n > 10 {
+        break
+    }
+    if n == 2 or n == 4 or n == 6 or n == 8 or n == 10 {
+        continue
+    }
+    odds += n
+}
+print(odds)
+"""
+    },
+    {
+        "name": "For-in loops",
+        "code": """
+let total = 0
+for x in [1, 2, 3, 4] {
+    total += x
+}
+print(total)
+for c in "Kyaro" {
+    print(c)
+}
+for i in range(2, 10, 3) {
+    print(i)
+}
+for i in range(5, 0, -2) {
+    print(i)
+}
+let count = 0
+for i in range(1000) {
+    count += 1
+}
+print(count)
+for i in range(10) {
+    if i == 2 {
+        continue
+    }
+    if i == 5 {
+        break
+    }
+    print(i)
+}
+func first_even(xs) {
+    for x in xs {
+        if x / 2 == floor(x / 2) {
+            return x
+        }
+    }
+    return null
+}
+print(first_even([3, 7, 10, 12]))
+let r = range(3)
+print(r)
+"""
+    },
+    {
+        "name": "Indexing with negative indices",
+        "code": """
+let numbers = [10, 20, 30, 40, 50]
+print(numbers[0])
+print(numbers[-1])
+print(numbers[len(numbers) - 2])
+let word = "Kyaro"
+print(word[1])
+print(word[-2])
+let grid = [[1, 2], [3, 4]]
+print(grid[1][-1])
+"""
+    },
+    {
+        "name": "Arithmetic, power and sequence operators",
+        "code": """
+print(7 % 3)
+print(-7 % 3)
+print(7 % -3)
+print(2 ** 10)
+print(2 ** 3 ** 2)
+print(2 ** -1)
+print(3 <= 3, 3 >= 4)
+print("ab" * 3)
+print(3 * "-")
+print([1, 2] + [3])
+print([0] * 4)
+print(2 * [1, 2])
+print("apple" < "banana", "b" >= "abc")
+print([1, 2, 3] < [1, 2, 4], [1, 2] < [1, 2, 0], [2] > [1, 9])
+print([1, [2, 3]] == [1, [2, 3]])
+print("x" * 0)
+"""
+    },
+    {
+        "name": "FizzBuzz",
+        "code": """
+for i in range(1, 31) {
+    if i % 15 == 0 {
+        print("FizzBuzz")
+    } elif i % 3 == 0 {
+        print("Fizz")
+    } elif i % 5 == 0 {
+        print("Buzz")
+    } else {
+        print(i)
+    }
+}
+"""
+    },
+    {
+        "name": "Or returns the deciding operand",
+        "code": """
+let name = ""
+print(name or "default")
+let given = "Kyaro"
+print(given or "default")
+print(null or 0 or "last")
+print(true and false)
+print(false or true)
+"""
+    },
+    {
+        "name": "Closures and outer variables",
+        "code": """
+func make_counter() {
+    let count = 0
+    func increment() {
+        count += 1
+        return count
+    }
+    return increment
+}
+let counter = make_counter()
+print(counter())
+print(counter())
+let total = 0
+func add(n) {
+    total = total + n
+}
+add(5)
+add(7)
+print(total)
+"""
+    },
+    {
+        "name": "Lists are shared and mutated in place",
+        "code": """
+let a = [3, 1, 2]
+let b = a
+append(a, 4)
+print(b)
+print(pop(a))
+insert(a, 0, 7)
+remove(a, 1)
+print(a)
+sort(a)
+print(a)
+reverse(a)
+extend(a, [5, 6])
+print(a)
+let c = copy(a)
+clear(a)
+print(len(a))
+print(len(c))
+func fill(list) {
+    push(list, "x")
+}
+fill(a)
+print(a)
+"""
+    },
+    {
+        "name": "Integers and floats",
+        "code": """
+let product = 1
+for i in range(1, 26) {
+    product = product * i
+}
+print(product)
+print(2 ** 70)
+print(10 / 2)
+print(7 / 2)
+print(-7 % 3)
+print(int("42") + 1)
+print(int(3.9))
+print(float(3))
+print(type(1))
+print(type(1.5))
+"""
+    },
+    {
+        "name": "Unicode strings",
+        "code": """
+let city = "São Paulo"
+print(len(city))
+print(city[1])
+print(city.upper())
+print(city.lower())
+print("a,b,c".split(","))
+"""
+    },
+    {
+        "name": "NaN comparisons",
+        "code": """
+let nan = float("nan")
+print(nan < 1)
+print(nan >= 1)
+print(1 <= nan)
+print(nan == nan)
+print(nan != nan)
+print([nan] < [1])
+print(sorted([3, 1, 2]))
 """
     }
 ]